@@ -7,9 +7,12 @@ use web_sys;
 
 use crate::components::header::Header;
 use crate::components::ui::dialog::Dialog;
-use crate::routes::home::get_posts;
-use crate::routes::home::HomePage;
+use crate::routes::archive::ArchivePage;
+use crate::routes::home::get_post_titles;
+use crate::routes::home::{HomePage, InfiniteScroll};
 use crate::routes::post::PostPage;
+use crate::routes::series::{SeriesListPage, SeriesPage};
+use crate::routes::tags::{TagPage, TagsPage};
 
 #[component]
 pub fn App() -> impl IntoView {
@@ -17,7 +20,7 @@ pub fn App() -> impl IntoView {
 
     // Initialize settings from localStorage or defaults
     #[cfg(feature = "hydrate")]
-    let (animation_speed, theme_mode, population_density) = {
+    let (animation_speed, theme_mode, population_density, infinite_scroll) = {
         let get_from_storage = |key: &str, default: &str| -> String {
             if let Some(window) = web_sys::window() {
                 if let Ok(Some(storage)) = window.local_storage() {
@@ -36,19 +39,22 @@ pub fn App() -> impl IntoView {
         let population_density_val = get_from_storage("blog_population_density", "0.08")
             .parse::<f64>()
             .unwrap_or(0.08);
+        let infinite_scroll_val = get_from_storage("blog_infinite_scroll", "false") == "true";
 
         (
             RwSignal::new(animation_speed_val),
             RwSignal::new(theme_mode_val),
             RwSignal::new(population_density_val),
+            RwSignal::new(infinite_scroll_val),
         )
     };
 
     #[cfg(not(feature = "hydrate"))]
-    let (animation_speed, theme_mode, population_density) = (
+    let (animation_speed, theme_mode, population_density, infinite_scroll) = (
         RwSignal::new(100u64),
         RwSignal::new("system".to_string()),
         RwSignal::new(0.08f64),
+        RwSignal::new(false),
     );
 
     // Global settings dialog control
@@ -57,8 +63,8 @@ pub fn App() -> impl IntoView {
     // Global search query control
     let search_query = RwSignal::new(String::new());
 
-    // Global posts resource
-    let posts = create_resource(|| (), |_| async { get_posts().await });
+    // Global post titles resource, for search suggestions
+    let post_titles = create_resource(|| (), |_| async { get_post_titles().await });
 
     // Save settings to localStorage when they change
     #[cfg(feature = "hydrate")]
@@ -83,6 +89,12 @@ pub fn App() -> impl IntoView {
             save_to_storage("blog_population_density", &density.to_string());
         });
 
+        // Watch infinite scroll changes
+        create_effect(move |_| {
+            let enabled = infinite_scroll.get();
+            save_to_storage("blog_infinite_scroll", &enabled.to_string());
+        });
+
         // Watch theme mode changes
         create_effect(move |_| {
             let mode = theme_mode.get();
@@ -134,12 +146,13 @@ pub fn App() -> impl IntoView {
         theme_mode.set(mode);
     };
 
-    // Provide global context for animation speed, settings, search, posts, and population density
+    // Provide global context for animation speed, settings, search, post titles, population density and infinite scroll
     provide_context(animation_speed);
     provide_context(show_settings);
     provide_context(search_query);
-    provide_context(posts);
+    provide_context(post_titles);
     provide_context(population_density);
+    provide_context(InfiniteScroll(infinite_scroll));
 
     view! {
         <Stylesheet id="leptos" href="/pkg/blog.css"/>
@@ -147,15 +160,13 @@ pub fn App() -> impl IntoView {
         <Link rel="icon" type_="image/png" sizes="32x32" href="/favicon-32x32.png?v=2"/>
         <Link rel="apple-touch-icon" href="/favicon-192x192.png"/>
         <Link rel="manifest" href="/manifest.json"/>
+        <Link rel="alternate" type_="application/rss+xml" title="RSS" href="/rss.xml"/>
+        <Link rel="alternate" type_="application/atom+xml" title="Atom" href="/atom.xml"/>
         <Meta name="theme-color" content="#000000"/>
         <Title text="blog"/>
         <Meta name="description" content="A blog built with Leptos and Rust"/>
 
 
-        // Load Prism with autoloader only
-        <script src="https://cdnjs.cloudflare.com/ajax/libs/prism/1.29.0/prism.min.js"></script>
-        <script src="https://cdnjs.cloudflare.com/ajax/libs/prism/1.29.0/plugins/autoloader/prism-autoloader.min.js"></script>
-
         // Load Pyodide for Python execution
         <script src="https://cdn.jsdelivr.net/pyodide/v0.24.1/full/pyodide.js"></script>
 
@@ -195,50 +206,6 @@ pub fn App() -> impl IntoView {
             "#
         </script>
 
-        // Theme switching for syntax highlighting
-        <script>
-            r#"
-            // Make updateSyntaxTheme available globally immediately
-            window.updateSyntaxTheme = function() {
-                // Themes are now inline, so this doesn't need to do anything
-                console.log('Theme update called - inline CSS is already active');
-            };
-            
-            // Apply initial theme immediately
-            window.updateSyntaxTheme();
-            
-            // Watch for theme changes
-            const observer = new MutationObserver((mutations) => {
-                mutations.forEach((mutation) => {
-                    if (mutation.type === 'attributes' && mutation.attributeName === 'class') {
-                        window.updateSyntaxTheme();
-                        // Re-highlight all code blocks when theme changes
-                        if (window.Prism) {
-                            window.Prism.highlightAll();
-                        }
-                    }
-                });
-            });
-            
-            // Start observing theme changes
-            observer.observe(document.documentElement, {
-                attributes: true,
-                attributeFilter: ['class']
-            });
-            
-            // Just apply theme changes, let components handle their own highlighting
-            document.addEventListener('DOMContentLoaded', () => {
-                console.log('DOMContentLoaded: applying theme');
-                window.updateSyntaxTheme();
-            });
-            
-            window.addEventListener('load', () => {
-                console.log('Window load: applying theme');
-                window.updateSyntaxTheme();
-            });
-            "#
-        </script>
-
         // Prevent theme flash by applying theme immediately
         <script>
             r#"
@@ -511,13 +478,6 @@ pub fn App() -> impl IntoView {
                 return div.innerHTML;
             }
             
-            function highlightCode() {
-                // Trigger syntax highlighting for all code blocks
-                if (window.Prism) {
-                    window.Prism.highlightAll();
-                }
-            }
-            
             // Load Pyodide for Python support
             function loadPyodide() {
                 const script = document.createElement('script');
@@ -549,11 +509,47 @@ pub fn App() -> impl IntoView {
                             </main>
                         </>
                     }/>
-                    <Route path="/post/:slug" view=move || view! {
+                    // Rendered in full before responding, so an unknown slug can answer 404
+                    <Route path="/post/:slug" ssr=SsrMode::Async view=move || view! {
                         <main class="min-h-screen">
                             <PostPage/>
                         </main>
                     }/>
+                    <Route path="/tags" view=move || view! {
+                        <main class="min-h-screen">
+                            <TagsPage/>
+                        </main>
+                    }/>
+                    <Route path="/tag/:tag" view=move || view! {
+                        <main class="min-h-screen">
+                            <TagPage/>
+                        </main>
+                    }/>
+                    <Route path="/archive" view=move || view! {
+                        <main class="min-h-screen">
+                            <ArchivePage/>
+                        </main>
+                    }/>
+                    <Route path="/archive/:year" view=move || view! {
+                        <main class="min-h-screen">
+                            <ArchivePage/>
+                        </main>
+                    }/>
+                    <Route path="/archive/:year/:month" view=move || view! {
+                        <main class="min-h-screen">
+                            <ArchivePage/>
+                        </main>
+                    }/>
+                    <Route path="/series" view=move || view! {
+                        <main class="min-h-screen">
+                            <SeriesListPage/>
+                        </main>
+                    }/>
+                    <Route path="/series/:series" view=move || view! {
+                        <main class="min-h-screen">
+                            <SeriesPage/>
+                        </main>
+                    }/>
                 </Routes>
             </div>
 
@@ -562,6 +558,7 @@ pub fn App() -> impl IntoView {
                 show_settings=show_settings
                 animation_speed=animation_speed
                 population_density=population_density
+                infinite_scroll=infinite_scroll
                 theme_mode=theme_mode
                 on_theme_change=Callback::new(handle_theme_change)
             />
@@ -5,8 +5,6 @@ use wasm_bindgen::{closure::Closure, JsCast};
 #[cfg(feature = "hydrate")]
 use web_sys::{Event, HtmlInputElement, KeyboardEvent};
 
-use crate::components::post::BlogPost;
-
 #[component]
 pub fn Header(
     #[prop(optional)] _on_animation_speed_change: Option<Callback<u64>>,
@@ -16,8 +14,8 @@ pub fn Header(
         use_context::<RwSignal<String>>().expect("search_query context not provided");
     let show_settings =
         use_context::<RwSignal<bool>>().expect("show_settings context not provided");
-    let posts = use_context::<Resource<(), Result<Vec<BlogPost>, ServerFnError>>>()
-        .expect("posts context not provided");
+    let titles = use_context::<Resource<(), Result<Vec<String>, ServerFnError>>>()
+        .expect("post titles context not provided");
     let theme_mode = RwSignal::new("system".to_string());
     let is_scrolled = RwSignal::new(false);
     // Generate inline text suggestion based on current query
@@ -27,14 +25,13 @@ pub fn Header(
         if query.is_empty() {
             String::new()
         } else {
-            posts
+            titles
                 .get()
-                .and_then(|posts_result| posts_result.ok())
-                .and_then(|posts| {
-                    posts
+                .and_then(|titles_result| titles_result.ok())
+                .and_then(|titles| {
+                    titles
                         .into_iter()
-                        .find(|post| post.title.to_lowercase().starts_with(&query.to_lowercase()))
-                        .map(|post| post.title.clone())
+                        .find(|title| title.to_lowercase().starts_with(&query.to_lowercase()))
                 })
                 .unwrap_or_default()
         }
@@ -0,0 +1,751 @@
+use super::CodeLanguage;
+
+// Lexical rules for one language. Anything the rules don't cover is left unstyled.
+struct Syntax {
+    keywords: &'static [&'static str],
+    builtins: &'static [&'static str],
+    constants: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    // Characters besides alphanumerics and `_` that may appear in identifiers
+    ident_chars: &'static [char],
+    // Capitalised identifiers are types
+    capitalized_types: bool,
+    // `'a` is a lifetime unless it closes like a char literal
+    lifetimes: bool,
+    // Keywords match regardless of case, as in SQL
+    case_insensitive: bool,
+}
+
+const C_LIKE_OPERATORS: &str = "+-*/%=&|<>!^~?:@";
+const PUNCTUATION: &str = "{}[]();,.";
+
+const RUST: Syntax = Syntax {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+        "unsafe", "use", "where", "while",
+    ],
+    builtins: &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8",
+        "u16", "u32", "u64", "u128", "usize", "String", "Vec", "Option", "Result", "Box",
+    ],
+    constants: &["true", "false", "None", "Some", "Ok", "Err"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    ident_chars: &[],
+    capitalized_types: true,
+    lifetimes: true,
+    case_insensitive: false,
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    keywords: &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "let",
+        "new",
+        "of",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "try",
+        "typeof",
+        "var",
+        "void",
+        "while",
+        "yield",
+        // TypeScript
+        "abstract",
+        "as",
+        "declare",
+        "enum",
+        "implements",
+        "interface",
+        "keyof",
+        "namespace",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "type",
+    ],
+    builtins: &[
+        "Array", "Boolean", "Date", "Error", "JSON", "Map", "Math", "Number", "Object", "Promise",
+        "RegExp", "Set", "String", "Symbol", "console", "document", "window", "any", "boolean",
+        "never", "number", "string", "unknown",
+    ],
+    constants: &["true", "false", "null", "undefined", "NaN", "Infinity"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    ident_chars: &['$'],
+    capitalized_types: true,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const PYTHON: Syntax = Syntax {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is",
+        "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with",
+        "yield", "self",
+    ],
+    builtins: &[
+        "abs",
+        "bool",
+        "dict",
+        "enumerate",
+        "float",
+        "input",
+        "int",
+        "isinstance",
+        "len",
+        "list",
+        "map",
+        "max",
+        "min",
+        "open",
+        "print",
+        "range",
+        "set",
+        "sorted",
+        "str",
+        "sum",
+        "tuple",
+        "type",
+        "zip",
+    ],
+    constants: &["True", "False", "None"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    ident_chars: &[],
+    capitalized_types: true,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const C: Syntax = Syntax {
+    keywords: &[
+        "auto",
+        "break",
+        "case",
+        "class",
+        "const",
+        "constexpr",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "explicit",
+        "extern",
+        "for",
+        "friend",
+        "goto",
+        "if",
+        "inline",
+        "namespace",
+        "new",
+        "operator",
+        "private",
+        "protected",
+        "public",
+        "register",
+        "return",
+        "sizeof",
+        "static",
+        "struct",
+        "switch",
+        "template",
+        "this",
+        "throw",
+        "try",
+        "catch",
+        "typedef",
+        "typename",
+        "union",
+        "using",
+        "virtual",
+        "volatile",
+        "while",
+        "#include",
+        "#define",
+        "#ifdef",
+        "#ifndef",
+        "#endif",
+        "#if",
+        "#else",
+        "#pragma",
+    ],
+    builtins: &[
+        "bool", "char", "double", "float", "int", "long", "short", "signed", "unsigned", "void",
+        "size_t", "std", "string", "vector", "printf", "malloc", "free",
+    ],
+    constants: &["true", "false", "NULL", "nullptr"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    ident_chars: &['#'],
+    capitalized_types: false,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const GO: Syntax = Syntax {
+    keywords: &[
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "fallthrough",
+        "for",
+        "func",
+        "go",
+        "goto",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "type",
+        "var",
+    ],
+    builtins: &[
+        "bool", "byte", "error", "float32", "float64", "int", "int32", "int64", "rune", "string",
+        "uint", "uint8", "uint32", "uint64", "append", "cap", "len", "make", "new", "panic", "fmt",
+    ],
+    constants: &["true", "false", "nil", "iota"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    ident_chars: &[],
+    capitalized_types: false,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const JVM: Syntax = Syntax {
+    keywords: &[
+        "abstract",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "continue",
+        "def",
+        "default",
+        "do",
+        "else",
+        "enum",
+        "extends",
+        "final",
+        "finally",
+        "for",
+        "fun",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "interface",
+        "is",
+        "match",
+        "new",
+        "object",
+        "override",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "sealed",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "throws",
+        "trait",
+        "try",
+        "val",
+        "var",
+        "void",
+        "when",
+        "while",
+        "with",
+        "yield",
+    ],
+    builtins: &[
+        "boolean", "byte", "char", "double", "float", "int", "long", "short", "String", "System",
+        "println",
+    ],
+    constants: &["true", "false", "null"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    ident_chars: &[],
+    capitalized_types: true,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const RUBY: Syntax = Syntax {
+    keywords: &[
+        "alias",
+        "and",
+        "begin",
+        "break",
+        "case",
+        "class",
+        "def",
+        "defined?",
+        "do",
+        "else",
+        "elsif",
+        "end",
+        "ensure",
+        "for",
+        "if",
+        "in",
+        "module",
+        "next",
+        "not",
+        "or",
+        "redo",
+        "rescue",
+        "retry",
+        "return",
+        "self",
+        "super",
+        "then",
+        "unless",
+        "until",
+        "when",
+        "while",
+        "yield",
+        "require",
+        "attr_accessor",
+    ],
+    builtins: &["puts", "print", "p", "lambda", "proc", "raise"],
+    constants: &["true", "false", "nil"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    ident_chars: &['?', '!', '@'],
+    capitalized_types: true,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const PHP: Syntax = Syntax {
+    keywords: &[
+        "abstract",
+        "as",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "echo",
+        "else",
+        "elseif",
+        "extends",
+        "final",
+        "foreach",
+        "for",
+        "function",
+        "if",
+        "implements",
+        "interface",
+        "namespace",
+        "new",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "static",
+        "switch",
+        "throw",
+        "try",
+        "use",
+        "while",
+        "fn",
+    ],
+    builtins: &["array", "count", "isset", "print", "strlen", "var_dump"],
+    constants: &["true", "false", "null", "TRUE", "FALSE", "NULL"],
+    line_comments: &["//", "#"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    ident_chars: &['$'],
+    capitalized_types: true,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const PERL: Syntax = Syntax {
+    keywords: &[
+        "else", "elsif", "for", "foreach", "if", "last", "local", "my", "next", "our", "package",
+        "return", "sub", "unless", "until", "use", "while",
+    ],
+    builtins: &[
+        "print", "printf", "push", "shift", "split", "join", "keys", "die",
+    ],
+    constants: &[],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    ident_chars: &['$', '@', '%'],
+    capitalized_types: false,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const LUA: Syntax = Syntax {
+    keywords: &[
+        "and", "break", "do", "else", "elseif", "end", "for", "function", "goto", "if", "in",
+        "local", "not", "or", "repeat", "return", "then", "until", "while",
+    ],
+    builtins: &[
+        "ipairs", "pairs", "print", "require", "string", "table", "tostring", "type",
+    ],
+    constants: &["true", "false", "nil"],
+    line_comments: &["--"],
+    block_comment: Some(("--[[", "]]")),
+    quotes: &['"', '\''],
+    ident_chars: &[],
+    capitalized_types: false,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const HASKELL: Syntax = Syntax {
+    keywords: &[
+        "case", "class", "data", "deriving", "do", "else", "if", "import", "in", "instance", "let",
+        "module", "newtype", "of", "then", "type", "where",
+    ],
+    builtins: &[
+        "map", "filter", "foldr", "foldl", "putStrLn", "print", "show", "return",
+    ],
+    constants: &["True", "False", "Nothing", "Just"],
+    line_comments: &["--"],
+    block_comment: Some(("{-", "-}")),
+    quotes: &['"'],
+    ident_chars: &['\''],
+    capitalized_types: true,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const ELIXIR: Syntax = Syntax {
+    keywords: &[
+        "after",
+        "case",
+        "catch",
+        "cond",
+        "def",
+        "defmodule",
+        "defp",
+        "do",
+        "else",
+        "end",
+        "fn",
+        "for",
+        "if",
+        "import",
+        "receive",
+        "rescue",
+        "try",
+        "unless",
+        "use",
+        "when",
+        "with",
+        "alias",
+        "require",
+    ],
+    builtins: &["IO", "Enum", "Map", "List", "String"],
+    constants: &["true", "false", "nil"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    ident_chars: &['?', '!'],
+    capitalized_types: true,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const BASH: Syntax = Syntax {
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "return", "then", "until", "while",
+    ],
+    builtins: &[
+        "cd", "echo", "exit", "printf", "read", "source", "cargo", "curl", "git", "npm", "sudo",
+    ],
+    constants: &["true", "false"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    ident_chars: &['$', '-'],
+    capitalized_types: false,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+const SQL: Syntax = Syntax {
+    keywords: &[
+        "select", "from", "where", "insert", "into", "values", "update", "set", "delete", "create",
+        "table", "drop", "alter", "join", "left", "right", "inner", "outer", "on", "group", "by",
+        "order", "having", "limit", "offset", "as", "and", "or", "not", "in", "is", "primary",
+        "key", "index", "distinct", "union", "with",
+    ],
+    builtins: &[
+        "count", "sum", "avg", "min", "max", "integer", "text", "varchar", "boolean",
+    ],
+    constants: &["null", "true", "false"],
+    line_comments: &["--"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    ident_chars: &[],
+    capitalized_types: false,
+    lifetimes: false,
+    case_insensitive: true,
+};
+
+// JSON, TOML and YAML: strings, numbers, literals and comments are all there is to color
+const DATA: Syntax = Syntax {
+    keywords: &[],
+    builtins: &[],
+    constants: &["true", "false", "null", "yes", "no"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    ident_chars: &['-'],
+    capitalized_types: false,
+    lifetimes: false,
+    case_insensitive: false,
+};
+
+fn syntax_for(language: &str) -> Option<&'static Syntax> {
+    let syntax = match CodeLanguage::from_str(language)? {
+        CodeLanguage::Rust => &RUST,
+        CodeLanguage::JavaScript | CodeLanguage::TypeScript => &JAVASCRIPT,
+        CodeLanguage::Python => &PYTHON,
+        CodeLanguage::C | CodeLanguage::Cpp => &C,
+        CodeLanguage::Go => &GO,
+        CodeLanguage::Java | CodeLanguage::Kotlin | CodeLanguage::Scala => &JVM,
+        CodeLanguage::Ruby => &RUBY,
+        CodeLanguage::PHP => &PHP,
+        CodeLanguage::Perl => &PERL,
+        CodeLanguage::Lua => &LUA,
+        CodeLanguage::Haskell => &HASKELL,
+        CodeLanguage::Elixir => &ELIXIR,
+        CodeLanguage::Bash => &BASH,
+        CodeLanguage::Other(other) => match other.to_lowercase().as_str() {
+            "jsx" | "tsx" | "json5" => &JAVASCRIPT,
+            "sql" | "postgres" | "sqlite" => &SQL,
+            "json" | "toml" | "yaml" | "yml" | "ini" => &DATA,
+            "zsh" | "console" | "shell-session" => &BASH,
+            _ => return None,
+        },
+    };
+    Some(syntax)
+}
+
+/// Highlight `code` as HTML, wrapping each token in a `<span class="token ...">`.
+///
+/// Runs identically during SSR and hydration, so the markup matches on both sides.
+/// Unknown languages come back HTML-escaped but otherwise untouched.
+pub fn highlight(code: &str, language: &str) -> String {
+    let Some(syntax) = syntax_for(language) else {
+        return html_escape::encode_text(code).to_string();
+    };
+
+    let mut html = String::with_capacity(code.len() * 2);
+    let mut rest = code;
+
+    while let Some(c) = rest.chars().next() {
+        let (class, len) = next_token(syntax, rest, c);
+        let (token, tail) = rest.split_at(len);
+        match class {
+            Some(class) => {
+                html.push_str(r#"<span class="token "#);
+                html.push_str(class);
+                html.push_str(r#"">"#);
+                html.push_str(&html_escape::encode_text(token));
+                html.push_str("</span>");
+            }
+            None => html.push_str(&html_escape::encode_text(token)),
+        }
+        rest = tail;
+    }
+
+    html
+}
+
+// Class and byte length of the token at the start of `text`
+fn next_token(syntax: &Syntax, text: &str, c: char) -> (Option<&'static str>, usize) {
+    if let Some((open, close)) = syntax.block_comment {
+        if let Some(body) = text.strip_prefix(open) {
+            let len = body
+                .find(close)
+                .map_or(text.len(), |end| open.len() + end + close.len());
+            return (Some("comment"), len);
+        }
+    }
+    if syntax
+        .line_comments
+        .iter()
+        .any(|marker| text.starts_with(marker))
+    {
+        return (Some("comment"), text.find('\n').unwrap_or(text.len()));
+    }
+
+    if syntax.quotes.contains(&c) {
+        if c == '\'' && syntax.lifetimes {
+            if let Some(len) = lifetime_len(text) {
+                return (Some("symbol"), len);
+            }
+        }
+        return (Some("string"), string_len(text, c));
+    }
+
+    if c.is_ascii_digit() {
+        return (Some("number"), number_len(text));
+    }
+
+    if is_ident_start(syntax, c) {
+        let len = text
+            .char_indices()
+            .find(|(_, ch)| !is_ident_char(syntax, *ch))
+            .map_or(text.len(), |(i, _)| i);
+        let word = &text[..len];
+        let after = text[len..].trim_start();
+
+        let is_keyword = if syntax.case_insensitive {
+            syntax.keywords.contains(&word.to_lowercase().as_str())
+        } else {
+            syntax.keywords.contains(&word)
+        };
+
+        let class = if is_keyword {
+            Some("keyword")
+        } else if syntax.constants.contains(&word) {
+            Some("boolean")
+        } else if syntax.builtins.contains(&word) {
+            Some("builtin")
+        } else if after.starts_with('(') || (syntax.lifetimes && after.starts_with('!')) {
+            Some("function")
+        } else if syntax.capitalized_types && word.starts_with(|ch: char| ch.is_uppercase()) {
+            Some("class-name")
+        } else {
+            None
+        };
+        return (class, len);
+    }
+
+    let len = c.len_utf8();
+    if C_LIKE_OPERATORS.contains(c) {
+        (Some("operator"), len)
+    } else if PUNCTUATION.contains(c) {
+        (Some("punctuation"), len)
+    } else {
+        (None, len)
+    }
+}
+
+fn is_ident_start(syntax: &Syntax, c: char) -> bool {
+    c.is_alphabetic() || c == '_' || (syntax.ident_chars.contains(&c) && c != '-' && c != '\'')
+}
+
+fn is_ident_char(syntax: &Syntax, c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || syntax.ident_chars.contains(&c)
+}
+
+// Up to and including the closing quote, skipping escaped characters
+fn string_len(text: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return i + c.len_utf8(),
+            // Unterminated single-line quotes stop at the end of the line
+            '\n' if quote != '`' && quote != '"' => return i,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+// `'a` or `'static`, as opposed to the char literals `'a'` and `'\n'`
+fn lifetime_len(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1);
+    let (_, first) = chars.next()?;
+    if !(first.is_alphabetic() || first == '_') {
+        return None;
+    }
+    let end = chars
+        .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+        .map_or(text.len(), |(i, _)| i);
+    if text[end..].starts_with('\'') {
+        None
+    } else {
+        Some(end)
+    }
+}
+
+// Digits, hex/float/exponent letters and separators, stopping before a `..` range
+fn number_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut len = 0;
+    while len < bytes.len() {
+        let b = bytes[len];
+        let is_range = b == b'.' && bytes.get(len + 1) == Some(&b'.');
+        let is_method = b == b'.' && bytes.get(len + 1).is_some_and(|n| n.is_ascii_alphabetic());
+        if !(b.is_ascii_alphanumeric() || b == b'_' || b == b'.') || is_range || is_method {
+            break;
+        }
+        len += 1;
+    }
+    len
+}
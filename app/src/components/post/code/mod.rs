@@ -1,5 +1,7 @@
 pub mod executor;
+pub mod highlight;
 pub mod runner;
 
 pub use executor::*;
+pub use highlight::*;
 pub use runner::*;
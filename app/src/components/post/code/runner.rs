@@ -1,4 +1,4 @@
-use super::{CodeExecutor, CodeLanguage, ExecutionResult};
+use super::{highlight, CodeExecutor, CodeLanguage, ExecutionResult};
 use leptos::*;
 
 #[cfg(feature = "hydrate")]
@@ -33,37 +33,6 @@ pub fn CodeRunner(
 
     let code_id = format!("code-runner-{}", rand::random::<u32>());
 
-    // Use create_effect to run highlighting after hydration
-    #[cfg(feature = "hydrate")]
-    {
-        let highlight_id = code_id.clone();
-        create_effect(move |_| {
-            // Use a longer delay to ensure everything is ready
-            let id_clone = highlight_id.clone();
-            Timeout::new(500, move || {
-                let _ = js_sys::eval(&format!(
-                    "console.log('Effect highlighting: {}'); \
-                     if (window.updateSyntaxTheme) {{ \
-                         window.updateSyntaxTheme(); \
-                     }} \
-                     if (window.Prism && window.Prism.highlightElement) {{ \
-                         var element = document.querySelector('#{} code'); \
-                         if (element) {{ \
-                             console.log('Effect found element, highlighting:', element); \
-                             window.Prism.highlightElement(element); \
-                         }} else {{ \
-                             console.log('Effect - element not found: #{}'); \
-                         }} \
-                     }} else {{ \
-                         console.log('Effect - Prism not available'); \
-                     }}",
-                    id_clone, id_clone, id_clone
-                ));
-            })
-            .forget();
-        });
-    }
-
     let copy_code = {
         let _code_copy = code_clone.clone();
         move |_| {
@@ -233,8 +202,11 @@ pub fn CodeRunner(
                     }}
                 </div>
 
-                // Code content
-                <code class=format!("language-{}", language_clone)>{code}</code>
+                // Code content, highlighted the same way on the server and in the browser
+                <code
+                    class=format!("language-{}", language_clone)
+                    inner_html=highlight(&code, &language_clone)
+                ></code>
 
                 // Console output - minimal and clean
                 {move || if show_output.get() {
@@ -0,0 +1,241 @@
+use super::code::CodeRunner;
+use super::types::content_hash;
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiagramKind {
+    Mermaid,
+    Graphviz,
+}
+
+impl DiagramKind {
+    // Fenced block languages that are drawn instead of shown as code
+    pub fn from_language(language: &str) -> Option<Self> {
+        match language.trim().to_lowercase().as_str() {
+            "mermaid" => Some(Self::Mermaid),
+            "dot" | "graphviz" => Some(Self::Graphviz),
+            _ => None,
+        }
+    }
+
+    pub const fn language(self) -> &'static str {
+        match self {
+            Self::Mermaid => "mermaid",
+            Self::Graphviz => "dot",
+        }
+    }
+}
+
+// Cache key for a diagram's kind and source
+pub fn diagram_hash(kind: DiagramKind, source: &str) -> String {
+    content_hash(format!("{}\0{}", kind.language(), source).as_bytes())
+}
+
+// A rendered diagram, or its source as a plain code block when it couldn't be drawn
+#[component]
+pub fn Diagram(kind: DiagramKind, source: String, svg: Option<String>) -> impl IntoView {
+    match svg {
+        Some(svg) => view! {
+            <figure
+                class="diagram my-6 sm:my-7 md:my-8 flex justify-center overflow-x-auto"
+                role="img"
+                aria-label=format!("{} diagram", kind.language())
+                inner_html=svg
+            ></figure>
+        }
+        .into_view(),
+        None => view! {
+            <CodeRunner
+                code=source
+                language=kind.language().to_string()
+                show_copy=true
+                show_run=false
+            />
+        }
+        .into_view(),
+    }
+}
+
+#[cfg(feature = "ssr")]
+mod render {
+    use super::{diagram_hash, DiagramKind};
+    use std::collections::HashMap;
+    use std::fs::{self, File};
+    use std::path::PathBuf;
+    use std::process::{Command, Stdio};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{LazyLock, Mutex};
+    use std::time::{Duration, Instant};
+
+    // Results by hash. Diagrams that are themselves broken are cached as `None` so they aren't
+    // retried on every request; failures that may pass next time (a missing or hung
+    // mermaid-cli, I/O errors) are not cached
+    static CACHE: LazyLock<Mutex<HashMap<String, Option<String>>>> =
+        LazyLock::new(|| Mutex::new(HashMap::new()));
+
+    // How long mermaid-cli may take before it is killed
+    const MERMAID_TIMEOUT: Duration = Duration::from_secs(30);
+
+    // Numbers each mermaid-cli run's scratch directory within this process
+    static RUN: AtomicU64 = AtomicU64::new(0);
+
+    enum RenderError {
+        // The diagram source itself is at fault; rendering it again won't help
+        Invalid(String),
+        // The renderer couldn't run or didn't finish
+        Unavailable(String),
+    }
+
+    // Rendered SVGs are also kept on disk, since Mermaid renders are slow
+    fn cache_dir() -> PathBuf {
+        std::env::var("BLOG_DIAGRAM_CACHE")
+            .unwrap_or_else(|_| "data/diagrams".to_string())
+            .into()
+    }
+
+    /// Renders a diagram to inline SVG, or `None` if it can't be drawn.
+    pub fn render_diagram(kind: DiagramKind, source: &str) -> Option<String> {
+        let hash = diagram_hash(kind, source);
+        if let Some(cached) = CACHE.lock().unwrap().get(&hash) {
+            return cached.clone();
+        }
+
+        let path = cache_dir().join(format!("{}.svg", hash));
+        if let Ok(svg) = fs::read_to_string(&path) {
+            CACHE.lock().unwrap().insert(hash, Some(svg.clone()));
+            return Some(svg);
+        }
+
+        let rendered = match kind {
+            DiagramKind::Graphviz => render_dot(source, &hash),
+            DiagramKind::Mermaid => render_mermaid(source, &hash),
+        };
+        let svg = match rendered {
+            Ok(svg) => {
+                let written = fs::create_dir_all(cache_dir()).and_then(|()| fs::write(&path, &svg));
+                if let Err(e) = written {
+                    tracing::warn!("Could not cache diagram {}: {}", path.display(), e);
+                }
+                Some(svg)
+            }
+            Err(RenderError::Invalid(e)) => {
+                tracing::warn!("Could not render {} diagram: {}", kind.language(), e);
+                None
+            }
+            Err(RenderError::Unavailable(e)) => {
+                tracing::warn!("Could not render {} diagram: {}", kind.language(), e);
+                return None;
+            }
+        };
+
+        CACHE.lock().unwrap().insert(hash, svg.clone());
+        svg
+    }
+
+    fn render_dot(source: &str, hash: &str) -> Result<String, RenderError> {
+        use layout::backends::svg::SVGWriter;
+        use layout::gv::{DotParser, GraphBuilder};
+
+        let graph = DotParser::new(source)
+            .process()
+            .map_err(RenderError::Invalid)?;
+        // The layout engine asserts on some malformed graphs rather than returning an error
+        let svg = std::panic::catch_unwind(|| {
+            let mut builder = GraphBuilder::new();
+            builder.visit_graph(&graph);
+            let mut visual = builder.get();
+            let mut writer = SVGWriter::new();
+            visual.do_it(false, false, false, &mut writer);
+            writer.finalize()
+        })
+        .map_err(|_| RenderError::Invalid("layout failed".to_string()))?;
+
+        // Drop the XML prolog and scope ids so several diagrams can share a page
+        let start = svg
+            .find("<svg")
+            .ok_or_else(|| RenderError::Invalid("no SVG output".to_string()))?;
+        let svg = &svg[start..];
+        let prefix = format!("d{}-", &hash[..8]);
+        Ok(svg
+            .replace("id=\"", &format!("id=\"{}", prefix))
+            .replace("url(#", &format!("url(#{}", prefix))
+            .replace("href=\"#", &format!("href=\"#{}", prefix)))
+    }
+
+    // Mermaid has no Rust renderer, so this shells out to mermaid-cli when it is installed
+    fn render_mermaid(source: &str, hash: &str) -> Result<String, RenderError> {
+        // A scratch directory per run, so concurrent renders of the same diagram don't
+        // overwrite each other's files
+        let dir = std::env::temp_dir().join(format!(
+            "blog-diagram-{}-{}",
+            std::process::id(),
+            RUN.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).map_err(|e| RenderError::Unavailable(e.to_string()))?;
+        let result = run_mermaid(source, hash, &dir);
+        let _ = fs::remove_dir_all(&dir);
+        result
+    }
+
+    fn run_mermaid(source: &str, hash: &str, dir: &std::path::Path) -> Result<String, RenderError> {
+        let unavailable = |e: std::io::Error| RenderError::Unavailable(e.to_string());
+        let cli = std::env::var("BLOG_MERMAID_CLI").unwrap_or_else(|_| "mmdc".to_string());
+        let input = dir.join("diagram.mmd");
+        let output = dir.join("diagram.svg");
+        let config = dir.join("config.json");
+        let errors = dir.join("stderr.log");
+        fs::write(&input, source).map_err(unavailable)?;
+        // Strict mode escapes label HTML and drops click handlers; Mermaid doesn't let `%%{init}%%`
+        // directives in the diagram change it, so the SVG is safe to inline for untrusted posts
+        fs::write(&config, r#"{"securityLevel":"strict"}"#).map_err(unavailable)?;
+
+        let id = format!("mermaid-{}", hash);
+        let mut command = Command::new(&cli);
+        command
+            .arg("-i")
+            .arg(&input)
+            .arg("-o")
+            .arg(&output)
+            .args(["-b", "transparent", "-I", &id])
+            .arg("-c")
+            .arg(&config)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            // A file rather than a pipe, which could fill up while nothing reads it
+            .stderr(File::create(&errors).map_err(unavailable)?);
+        // Browser options for mermaid-cli's headless Chromium, e.g. `--no-sandbox` in containers
+        if let Ok(puppeteer) = std::env::var("BLOG_MERMAID_PUPPETEER_CONFIG") {
+            command.arg("-p").arg(puppeteer);
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| RenderError::Unavailable(format!("could not run {}: {}", cli, e)))?;
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(unavailable)? {
+                break status;
+            }
+            if started.elapsed() > MERMAID_TIMEOUT {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(RenderError::Unavailable(format!(
+                    "{} did not finish within {}s",
+                    cli,
+                    MERMAID_TIMEOUT.as_secs()
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        };
+
+        if !status.success() {
+            let stderr = fs::read_to_string(&errors).unwrap_or_default();
+            return Err(RenderError::Invalid(stderr.trim().to_string()));
+        }
+        fs::read_to_string(&output).map_err(unavailable)
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub use render::render_diagram;
@@ -1,17 +1,25 @@
 use super::interactions::PostMetrics;
+use super::types::PostStatus;
 use crate::components::ui::badge::{Badge, BadgeSize, BadgeVariant};
 use crate::components::ui::button::{Button, ButtonVariant};
+use crate::routes::tags::tag_href;
 use chrono::{DateTime, Utc};
 use leptos::*;
+use leptos_router::A;
 
 #[component]
 pub fn PostHeader(
     slug: String,
     title: String,
     date: DateTime<Utc>,
+    updated: Option<DateTime<Utc>>,
     excerpt: String,
     tags: Vec<String>,
+    word_count: usize,
+    code_lines: usize,
+    reading_time_minutes: u32,
     initial_views: u64,
+    #[prop(optional)] status: PostStatus,
 ) -> impl IntoView {
     #[cfg_attr(not(feature = "hydrate"), allow(unused_variables))]
     let (is_shared, set_is_shared) = create_signal(false);
@@ -26,6 +34,29 @@ pub fn PostHeader(
                     </svg>
                     <span>{date.format("%B %d, %Y").to_string()}</span>
                 </div>
+                <div
+                    class="flex items-center gap-1.5 sm:gap-2"
+                    title=format!("{} words, {} lines of code", word_count, code_lines)
+                >
+                    <svg class="w-3 h-3 sm:w-4 sm:h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 8v4l3 3m6-3a9 9 0 11-18 0 9 9 0 0118 0z"></path>
+                    </svg>
+                    <span>{reading_time_minutes}" min read"</span>
+                </div>
+                // Only worth mentioning when the revision falls on a later day
+                {updated.filter(|updated| updated.date_naive() > date.date_naive()).map(|updated| view! {
+                    <div class="flex items-center gap-1.5 sm:gap-2">
+                        <svg class="w-3 h-3 sm:w-4 sm:h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                            <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M4 4v5h.582m15.356 2A8.001 8.001 0 004.582 9m0 0H9m11 11v-5h-.581m0 0a8.003 8.003 0 01-15.357-2m15.357 2H15"></path>
+                        </svg>
+                        <span>{format!("Updated {}", updated.format("%B %d, %Y"))}</span>
+                    </div>
+                })}
+                {(status != PostStatus::Published).then(|| view! {
+                    <Badge variant=BadgeVariant::Warning size=BadgeSize::Small>
+                        {status.as_str()}
+                    </Badge>
+                })}
             </div>
 
             <p class="text-base sm:text-lg md:text-xl text-muted-foreground mb-4 sm:mb-5 md:mb-6 leading-relaxed italic">{excerpt.clone()}</p>
@@ -33,9 +64,11 @@ pub fn PostHeader(
             <div class="flex flex-wrap gap-1.5 sm:gap-2 mb-4">
                 {tags.into_iter().map(|tag| {
                     view! {
-                        <Badge variant=BadgeVariant::Primary size=BadgeSize::Medium>
-                            {"#"}{tag}
-                        </Badge>
+                        <A href=tag_href(&tag)>
+                            <Badge variant=BadgeVariant::Primary size=BadgeSize::Medium>
+                                {"#"}{tag}
+                            </Badge>
+                        </A>
                     }
                 }).collect::<Vec<_>>()}
             </div>
@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+
+// Intrinsic size of a post image and the resized WebP copies available for it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    // (width, url) of each variant, narrowest first
+    pub variants: Vec<(u32, String)>,
+}
+
+// Posts are at most 56rem wide, so larger renditions are never picked
+const SIZES: &str = "(min-width: 56rem) 56rem, 100vw";
+
+const IMAGE_CLASSES: &str = "w-full h-auto border-2 border-border my-8";
+
+/// Markup for a Markdown image: a `<picture>` with a WebP `srcset` when variants exist,
+/// otherwise a plain lazily loaded `<img>`.
+pub fn image_html(url: &str, alt: &str, title: &str, info: Option<&ImageInfo>) -> String {
+    let mut img = format!(
+        r#"<img src="{}" alt="{}""#,
+        html_escape::encode_double_quoted_attribute(url),
+        html_escape::encode_double_quoted_attribute(alt)
+    );
+    if !title.is_empty() {
+        img.push_str(&format!(
+            r#" title="{}""#,
+            html_escape::encode_double_quoted_attribute(title)
+        ));
+    }
+    // Known dimensions let the browser reserve space before the image arrives
+    if let Some(info) = info {
+        img.push_str(&format!(
+            r#" width="{}" height="{}""#,
+            info.width, info.height
+        ));
+    }
+    img.push_str(&format!(
+        r#" loading="lazy" decoding="async" class="{}">"#,
+        IMAGE_CLASSES
+    ));
+
+    match info.filter(|info| !info.variants.is_empty()) {
+        Some(info) => {
+            let srcset = info
+                .variants
+                .iter()
+                .map(|(width, url)| format!("{} {}w", url, width))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                r#"<picture><source type="image/webp" srcset="{}" sizes="{}">{}</picture>"#,
+                html_escape::encode_double_quoted_attribute(&srcset),
+                SIZES,
+                img
+            )
+        }
+        None => img,
+    }
+}
+
+/// Wraps an image in a numbered `<figure>`, with `caption_html` after the number.
+pub fn figure_html(id: &str, number: usize, caption_html: &str, image_html: &str) -> String {
+    let caption = if caption_html.trim().is_empty() {
+        String::new()
+    } else {
+        format!(" {}", caption_html)
+    };
+    format!(
+        r#"<figure id="{}" class="my-8">{}<figcaption class="mt-3 text-sm text-muted-foreground text-center"><span class="font-semibold text-foreground">Figure {}.</span>{}</figcaption></figure>"#,
+        html_escape::encode_double_quoted_attribute(id),
+        image_html,
+        number,
+        caption
+    )
+}
+
+#[cfg(feature = "ssr")]
+mod process {
+    use super::ImageInfo;
+    use crate::components::post::markdown::resolve_asset_url;
+    use crate::components::post::render::clear_renders;
+    use crate::components::post::types::content_hash;
+    use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+    use pulldown_cmark::{Event, Parser, Tag};
+    use std::collections::{HashMap, HashSet};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{self, SyncSender};
+    use std::sync::{Arc, LazyLock, Mutex};
+
+    /// Directory served at `/assets/`, where post images live.
+    pub const ASSETS_DIR: &str = "posts";
+
+    // Variant widths in pixels; images are never upscaled
+    const VARIANT_WIDTHS: [u32; 3] = [480, 960, 1440];
+    // WebP only: every browser that decodes AVIF also decodes WebP, and encoding AVIF (rav1e
+    // through the `image` crate's `avif` feature) is many times slower and a heavy build
+    // dependency, for files only somewhat smaller
+    const WEBP_QUALITY: f32 = 80.0;
+
+    // Variants are generated by a fixed set of worker threads, fed from a bounded queue; images
+    // that don't fit in the queue are picked up again on a later request
+    const RESIZE_WORKERS: usize = 2;
+    const RESIZE_QUEUE_LEN: usize = 64;
+
+    // Escaped in file names put into URLs: everything but unreserved characters, so spaces
+    // and the commas that separate `srcset` candidates never appear as they are
+    const URL_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+        .remove(b'-')
+        .remove(b'_')
+        .remove(b'.')
+        .remove(b'~');
+
+    /// The file under `ASSETS_DIR` that an `/assets/...` URL points at, with escapes such as
+    /// `my%20photo.png` decoded. `None` for other URLs and for paths leaving the directory.
+    pub fn asset_path(url: &str) -> Option<PathBuf> {
+        let relative = percent_decode_str(url.strip_prefix("/assets/")?)
+            .decode_utf8()
+            .ok()?;
+        if relative.split('/').any(|part| part == "..") {
+            return None;
+        }
+        Some(Path::new(ASSETS_DIR).join(relative.as_ref()))
+    }
+
+    /// A file name escaped for use as one URL path segment.
+    pub fn url_segment(name: &str) -> String {
+        utf8_percent_encode(name, URL_SEGMENT).to_string()
+    }
+
+    /// Where resized variants are written, served at `/images/`.
+    pub fn image_cache_dir() -> PathBuf {
+        std::env::var("BLOG_IMAGE_CACHE")
+            .unwrap_or_else(|_| "data/images".to_string())
+            .into()
+    }
+
+    // Width and height, or `None` when the file couldn't be decoded
+    type Dimensions = Option<(u32, u32)>;
+
+    // Dimensions by source hash, so each image header is read once
+    static DIMENSIONS: LazyLock<Mutex<HashMap<String, Dimensions>>> =
+        LazyLock::new(|| Mutex::new(HashMap::new()));
+    // Source hashes whose variants are queued or being generated
+    static IN_PROGRESS: LazyLock<Mutex<HashSet<String>>> =
+        LazyLock::new(|| Mutex::new(HashSet::new()));
+    // Source hashes that could not be resized, so a broken file isn't retried on every request.
+    // Editing the file changes its hash and gives it another try.
+    static FAILED: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+    struct ResizeJob {
+        hash: String,
+        source: PathBuf,
+        targets: Vec<(u32, PathBuf)>,
+    }
+
+    static RESIZE_JOBS: LazyLock<SyncSender<ResizeJob>> = LazyLock::new(|| {
+        let (sender, receiver) = mpsc::sync_channel::<ResizeJob>(RESIZE_QUEUE_LEN);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..RESIZE_WORKERS {
+            let receiver = Arc::clone(&receiver);
+            std::thread::spawn(move || loop {
+                let job = receiver.lock().unwrap().recv();
+                match job {
+                    Ok(job) => resize(job),
+                    Err(_) => break,
+                }
+            });
+        }
+        sender
+    });
+
+    fn resize(job: ResizeJob) {
+        match write_variants(&job.source, job.targets) {
+            // Cached renders were made without the new variants
+            Ok(()) => clear_renders(),
+            Err(e) => {
+                tracing::warn!("Could not resize {}: {}", job.source.display(), e);
+                FAILED.lock().unwrap().insert(job.hash.clone());
+            }
+        }
+        IN_PROGRESS.lock().unwrap().remove(&job.hash);
+    }
+
+    /// Dimensions and ready variants of every local image in a post, keyed by resolved URL.
+    pub fn image_assets(content: &str) -> HashMap<String, ImageInfo> {
+        Parser::new(content)
+            .filter_map(|event| match event {
+                Event::Start(Tag::Image(_, url, _)) => Some(resolve_asset_url(&url, None)),
+                _ => None,
+            })
+            .filter_map(|url| Some((url.clone(), image_info(&url)?)))
+            .collect()
+    }
+
+    fn image_info(url: &str) -> Option<ImageInfo> {
+        let path = asset_path(url)?;
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        // SVGs scale on their own and GIFs would lose their animation
+        if !matches!(extension.as_str(), "png" | "jpg" | "jpeg" | "webp") {
+            return None;
+        }
+
+        // Name variants after the file's identity so edits produce new ones
+        let metadata = std::fs::metadata(&path).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_secs());
+        let hash = content_hash(
+            format!("{}\0{}\0{}", path.display(), metadata.len(), modified).as_bytes(),
+        );
+
+        let dimensions = *DIMENSIONS
+            .lock()
+            .unwrap()
+            .entry(hash.clone())
+            .or_insert_with(|| image::image_dimensions(&path).ok());
+        let (width, height) = dimensions?;
+
+        let widths = variant_widths(width);
+        let stem = path.file_stem()?.to_str()?;
+        let variant_name = |w: u32| format!("{}-{}-{}.webp", stem, hash, w);
+        let variant_path = |w: u32| image_cache_dir().join(variant_name(w));
+
+        let variants: Vec<(u32, String)> = widths
+            .iter()
+            .filter(|w| variant_path(**w).is_file())
+            .map(|w| (*w, format!("/images/{}", url_segment(&variant_name(*w)))))
+            .collect();
+        if variants.len() < widths.len()
+            && !FAILED.lock().unwrap().contains(&hash)
+            && IN_PROGRESS.lock().unwrap().insert(hash.clone())
+        {
+            let job = ResizeJob {
+                hash: hash.clone(),
+                source: path.clone(),
+                targets: widths.iter().map(|w| (*w, variant_path(*w))).collect(),
+            };
+            if RESIZE_JOBS.try_send(job).is_err() {
+                IN_PROGRESS.lock().unwrap().remove(&hash);
+            }
+        }
+
+        Some(ImageInfo {
+            width,
+            height,
+            variants,
+        })
+    }
+
+    // Every standard width below the original, plus the original width when it is in range
+    fn variant_widths(width: u32) -> Vec<u32> {
+        let largest = VARIANT_WIDTHS[VARIANT_WIDTHS.len() - 1];
+        let mut widths: Vec<u32> = VARIANT_WIDTHS.into_iter().filter(|w| *w < width).collect();
+        if width <= largest {
+            widths.push(width);
+        }
+        widths
+    }
+
+    fn write_variants(source: &Path, targets: Vec<(u32, PathBuf)>) -> Result<(), String> {
+        let image = image::open(source).map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(image_cache_dir()).map_err(|e| e.to_string())?;
+
+        for (width, target) in targets {
+            if target.is_file() {
+                continue;
+            }
+            let height = (u64::from(image.height()) * u64::from(width) / u64::from(image.width()))
+                .max(1) as u32;
+            let resized = image
+                .resize_exact(width, height, image::imageops::FilterType::Lanczos3)
+                .to_rgba8();
+            let encoded = webp::Encoder::from_rgba(&resized, width, height).encode(WEBP_QUALITY);
+
+            // Written under a temporary name so a half-written file is never served
+            let partial = target.with_extension("webp.partial");
+            std::fs::write(&partial, &*encoded).map_err(|e| e.to_string())?;
+            std::fs::rename(&partial, &target).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub use process::{asset_path, image_assets, image_cache_dir, url_segment, ASSETS_DIR};
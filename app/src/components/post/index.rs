@@ -0,0 +1,343 @@
+use super::render::{clear_renders, forget_render};
+use super::search::{to_hits, SearchIndex};
+use super::types::{
+    collect_series, get_post_metrics, parse_post, preview_drafts, sort_series, BlogPost,
+    PostParseError, PostSummary, RelatedPosts, SearchHit, Series, POSTS_DIR,
+};
+use chrono::Utc;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// In-memory view of every post in the posts directory, keyed by slug.
+///
+/// Metrics are not stored here; they are attached from the metrics store on every read.
+#[derive(Debug, Default)]
+pub struct PostIndex {
+    posts: HashMap<String, BlogPost>,
+    search: SearchIndex,
+    // Every series over all posts, rebuilt with the search index; parts that aren't listed
+    // are left out when read
+    series: Vec<Series>,
+    // Files that were skipped because they failed to parse
+    errors: HashMap<PathBuf, PostParseError>,
+}
+
+impl PostIndex {
+    pub fn load(dir: &Path) -> Self {
+        let mut index = Self::default();
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("Could not read posts directory {}: {}", dir.display(), e);
+                return index;
+            }
+        };
+
+        for entry in entries.flatten() {
+            index.refresh(&entry.path());
+        }
+        index.reindex();
+
+        index
+    }
+
+    /// All listed posts, newest first.
+    ///
+    /// Drafts, unlisted and scheduled posts are left out unless drafts are being previewed.
+    pub fn posts(&self) -> Vec<BlogPost> {
+        let now = Utc::now();
+        let preview = preview_drafts();
+        let mut posts: Vec<BlogPost> = self
+            .posts
+            .values()
+            .filter(|post| preview || post.is_listed(now))
+            .map(with_metrics)
+            .collect();
+        posts.sort_by_key(|post| std::cmp::Reverse(post.date));
+        posts
+    }
+
+    /// A single post by slug, including unlisted ones.
+    pub fn get(&self, slug: &str) -> Option<BlogPost> {
+        let now = Utc::now();
+        self.posts
+            .get(slug)
+            .filter(|post| preview_drafts() || post.is_published(now))
+            .map(with_metrics)
+    }
+
+    /// Full-text search over listed posts, best match first.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let now = Utc::now();
+        let preview = preview_drafts();
+        let ranked: Vec<(String, f32)> = self
+            .search
+            .search(query)
+            .into_iter()
+            .filter(|(slug, _)| {
+                self.posts
+                    .get(slug)
+                    .is_some_and(|post| preview || post.is_listed(now))
+            })
+            .collect();
+        let posts: Vec<BlogPost> = ranked
+            .iter()
+            .filter_map(|(slug, _)| self.get(slug))
+            .collect();
+        to_hits(query, ranked, &posts)
+    }
+
+    /// The listed posts either side of `slug` by date, and up to `RELATED_LIMIT` posts on
+    /// similar topics, scored by shared tags and body similarity.
+    ///
+    /// Parts of the same series are left out of the related posts; the series has its own
+    /// navigation.
+    pub fn related(&self, slug: &str) -> RelatedPosts {
+        let Some(post) = self.get(slug) else {
+            return RelatedPosts::default();
+        };
+        let listed = self.posts();
+
+        // `posts` is newest first
+        let (previous, next) = match listed.iter().position(|p| p.slug == slug) {
+            Some(position) => (
+                listed.get(position + 1).map(PostSummary::from),
+                position
+                    .checked_sub(1)
+                    .and_then(|i| listed.get(i))
+                    .map(PostSummary::from),
+            ),
+            None => (None, None),
+        };
+
+        let similarity: HashMap<String, f32> = self.search.similar(slug).into_iter().collect();
+        let mut scored: Vec<(f32, &BlogPost)> = listed
+            .iter()
+            .filter(|other| other.slug != slug)
+            .filter(|other| post.series.is_none() || other.series != post.series)
+            .map(|other| {
+                let score = TAG_OVERLAP_WEIGHT * tag_overlap(&post.tags, &other.tags)
+                    + similarity.get(&other.slug).copied().unwrap_or(0.0);
+                (score, other)
+            })
+            .filter(|(score, _)| *score >= MIN_RELATED_SCORE)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        RelatedPosts {
+            previous,
+            next,
+            related: scored
+                .into_iter()
+                .take(RELATED_LIMIT)
+                .map(|(_, post)| PostSummary::from(post))
+                .collect(),
+        }
+    }
+
+    /// Every series with a listed part, most recently continued first.
+    pub fn series(&self) -> Vec<Series> {
+        let now = Utc::now();
+        let preview = preview_drafts();
+        let mut series: Vec<Series> = self
+            .series
+            .iter()
+            .filter_map(|series| {
+                let parts: Vec<_> = series
+                    .parts
+                    .iter()
+                    .filter(|part| {
+                        self.posts
+                            .get(&part.slug)
+                            .is_some_and(|post| preview || post.is_listed(now))
+                    })
+                    .map(|part| {
+                        let mut part = part.clone();
+                        part.views = get_post_metrics(&part.slug).views;
+                        part
+                    })
+                    .collect();
+                (!parts.is_empty()).then(|| Series {
+                    name: series.name.clone(),
+                    slug: series.slug.clone(),
+                    parts,
+                })
+            })
+            .collect();
+        sort_series(&mut series);
+        series
+    }
+
+    /// Every post file that failed to parse, ordered by file name.
+    pub fn errors(&self) -> Vec<PostParseError> {
+        let mut errors: Vec<PostParseError> = self.errors.values().cloned().collect();
+        errors.sort_by(|a, b| a.file.cmp(&b.file));
+        errors
+    }
+
+    /// Plain-text summary of the posts that failed to parse, one `error:` line each, as
+    /// printed by `blog --check` and served at `/lint`.
+    pub fn lint_report(&self) -> String {
+        let errors = self.errors();
+        if errors.is_empty() {
+            return format!("{} posts OK\n", self.len());
+        }
+
+        let mut report = String::new();
+        for error in &errors {
+            report.push_str(&format!("error: {}\n", error));
+        }
+        report.push_str(&format!(
+            "{} of {} posts failed to parse\n",
+            errors.len(),
+            self.len() + errors.len()
+        ));
+        report
+    }
+
+    pub fn len(&self) -> usize {
+        self.posts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.posts.is_empty()
+    }
+
+    fn reindex(&mut self) {
+        // Posts scheduled for later count as listed once the index is next rebuilt
+        let now = Utc::now();
+        let preview = preview_drafts();
+        self.search =
+            SearchIndex::build(self.posts.values(), |post| preview || post.is_listed(now));
+        self.series = collect_series(self.posts.values());
+    }
+
+    // Re-read a single file, dropping it from the index if it was deleted or no longer parses.
+    // Returns the parse error, which callers log or report as they see fit
+    fn refresh(&mut self, path: &Path) -> Option<&PostParseError> {
+        if !is_post_file(path) {
+            return None;
+        }
+        let slug = path.file_stem().and_then(|s| s.to_str())?;
+
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| PostParseError {
+                file: path.display().to_string(),
+                field: None,
+                reason: format!("could not read file: {}", e),
+                line: None,
+            })
+            .and_then(|content| parse_post(&content, path));
+
+        match parsed {
+            Ok(post) => {
+                self.errors.remove(path);
+                self.posts.insert(post.slug.clone(), post);
+                None
+            }
+            Err(error) => {
+                self.posts.remove(slug);
+                forget_render(slug);
+                // A deleted file is not a parse failure
+                if path.exists() {
+                    let entry = self.errors.entry(path.to_path_buf()).insert_entry(error);
+                    Some(entry.into_mut())
+                } else {
+                    self.errors.remove(path);
+                    None
+                }
+            }
+        }
+    }
+}
+
+const RELATED_LIMIT: usize = 3;
+// A shared tag counts for about as much as a strong textual match
+const TAG_OVERLAP_WEIGHT: f32 = 0.5;
+// Below this, posts have too little in common to be worth suggesting
+const MIN_RELATED_SCORE: f32 = 0.05;
+
+// Jaccard index of two tag lists, ignoring case
+fn tag_overlap(a: &[String], b: &[String]) -> f32 {
+    let a: HashSet<String> = a.iter().map(|tag| tag.to_lowercase()).collect();
+    let b: HashSet<String> = b.iter().map(|tag| tag.to_lowercase()).collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f32 / union as f32
+}
+
+fn is_post_file(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("md")
+}
+
+fn with_metrics(post: &BlogPost) -> BlogPost {
+    let mut post = post.clone();
+    post.metrics = get_post_metrics(&post.slug);
+    post
+}
+
+// `blog --check` prints its own report, so only the served index logs skipped posts
+static POST_INDEX: LazyLock<Arc<RwLock<PostIndex>>> = LazyLock::new(|| {
+    let index = PostIndex::load(Path::new(POSTS_DIR));
+    for error in index.errors() {
+        tracing::warn!("Skipping post {}", error);
+    }
+    Arc::new(RwLock::new(index))
+});
+
+/// The shared post index, built from disk on first access.
+pub fn post_index() -> Arc<RwLock<PostIndex>> {
+    POST_INDEX.clone()
+}
+
+/// Keep `index` in sync with `dir` as posts are added, edited or deleted, and drop cached
+/// renders when files under `assets` (images, posters, subtitles) change.
+///
+/// The returned watcher stops delivering events when dropped.
+pub fn watch_posts(
+    index: Arc<RwLock<PostIndex>>,
+    dir: impl AsRef<Path>,
+    assets: impl AsRef<Path>,
+) -> notify::Result<RecommendedWatcher> {
+    // Canonical, so event paths can be told apart by directory
+    let dir = fs::canonicalize(dir)?;
+    let posts_dir = dir.clone();
+    let is_post = move |path: &Path| is_post_file(path) && path.parent() == Some(&posts_dir);
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+            Ok(event) => {
+                // Anything else is an asset that may end up in a render
+                if !event.kind.is_access() && event.paths.iter().any(|p| !is_post(p)) {
+                    clear_renders();
+                }
+
+                let changed: Vec<&PathBuf> = event.paths.iter().filter(|p| is_post(p)).collect();
+                if changed.is_empty() {
+                    return;
+                }
+
+                let mut index = index.write().unwrap();
+                for path in changed {
+                    tracing::debug!("Post changed on disk: {}", path.display());
+                    if let Some(error) = index.refresh(path) {
+                        tracing::warn!("Skipping post {}", error);
+                    }
+                }
+                index.reindex();
+            }
+            Err(e) => tracing::warn!("Post watcher error: {}", e),
+        })?;
+
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    match fs::canonicalize(assets) {
+        Ok(assets) => watcher.watch(&assets, RecursiveMode::Recursive)?,
+        Err(e) => tracing::warn!("Not watching assets: {}", e),
+    }
+    Ok(watcher)
+}
@@ -1,17 +1,27 @@
 use crate::components::ui::button::{Button, ButtonVariant};
 use leptos::*;
 
-// Component for displaying view count on post cards
+// Date and reading time on post cards, always shown so touch screens see them too; the
+// view count joins them on hover
 #[component]
-pub fn PostCardMetrics(views: u64) -> impl IntoView {
+pub fn PostCardMetrics(
+    date: chrono::DateTime<chrono::Utc>,
+    views: u64,
+    reading_time_minutes: u32,
+) -> impl IntoView {
     view! {
-        <div class="inline-flex items-center justify-center h-8 w-auto px-2 gap-1 text-xs sm:text-sm text-muted-foreground pointer-events-none opacity-0 group-hover:opacity-100 transition-opacity duration-200">
-            // Views only
-            <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
-                <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M15 12a3 3 0 11-6 0 3 3 0 016 0z"></path>
-                <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M2.458 12C3.732 7.943 7.523 5 12 5c4.478 0 8.268 2.943 9.542 7-1.274 4.057-5.064 7-9.542 7-4.477 0-8.268-2.943-9.542-7z"></path>
-            </svg>
-            <span>{views}" "{if views == 1 { "view" } else { "views" }}</span>
+        <div class="inline-flex items-center h-6 w-auto gap-1 text-xs sm:text-sm text-muted-foreground pointer-events-none">
+            <time datetime=date.to_rfc3339()>{date.format("%b %d, %Y").to_string()}</time>
+            <span aria-hidden="true">"·"</span>
+            <span>{reading_time_minutes}" min read"</span>
+            <span class="inline-flex items-center gap-1 opacity-0 group-hover:opacity-100 transition-opacity duration-200">
+                <span aria-hidden="true">"·"</span>
+                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M15 12a3 3 0 11-6 0 3 3 0 016 0z"></path>
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M2.458 12C3.732 7.943 7.523 5 12 5c4.478 0 8.268 2.943 9.542 7-1.274 4.057-5.064 7-9.542 7-4.477 0-8.268-2.943-9.542-7z"></path>
+                </svg>
+                <span>{views}" "{if views == 1 { "view" } else { "views" }}</span>
+            </span>
         </div>
     }
 }
@@ -64,6 +74,61 @@ pub fn PostMetrics(slug: String, initial_views: u64) -> impl IntoView {
     }
 }
 
+// Copy the absolute URL of a post, or of one of its sections, to the clipboard
+#[cfg(feature = "hydrate")]
+pub async fn copy_post_link(slug: &str, section: Option<&str>) {
+    if let Some(window) = web_sys::window() {
+        let mut full_url = format!("{}/post/{}", window.location().origin().unwrap(), slug);
+        if let Some(section) = section {
+            full_url.push('#');
+            full_url.push_str(section);
+        }
+
+        let navigator = window.navigator();
+        let clipboard = navigator.clipboard();
+        let promise = clipboard.write_text(&full_url);
+        let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+    }
+}
+
+// Heading anchors copy a link to their section instead of jumping to it, and show
+// "Copied" for a moment afterwards
+pub fn use_section_links(slug: String) {
+    #[cfg(feature = "hydrate")]
+    {
+        use wasm_bindgen::JsCast;
+
+        let handle = window_event_listener(ev::click, move |event| {
+            let Some(anchor) = event
+                .target()
+                .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                .and_then(|element| element.closest("a.heading-anchor").ok().flatten())
+            else {
+                return;
+            };
+            let Some(section) = anchor
+                .get_attribute("href")
+                .and_then(|href| href.strip_prefix('#').map(str::to_string))
+            else {
+                return;
+            };
+            event.prevent_default();
+
+            let slug = slug.clone();
+            spawn_local(async move {
+                copy_post_link(&slug, Some(&section)).await;
+                let _ = anchor.set_attribute("data-copied", "");
+                gloo_timers::future::TimeoutFuture::new(2000).await;
+                let _ = anchor.remove_attribute("data-copied");
+            });
+        });
+        on_cleanup(move || handle.remove());
+    }
+
+    #[cfg(not(feature = "hydrate"))]
+    let _ = slug;
+}
+
 // Component for post interactions at the bottom of post pages
 #[component]
 pub fn PostInteractions(slug: String) -> impl IntoView {
@@ -87,20 +152,7 @@ pub fn PostInteractions(slug: String) -> impl IntoView {
 
             spawn_local(async move {
                 #[cfg(feature = "hydrate")]
-                {
-                    if let Some(window) = web_sys::window() {
-                        let full_url = format!(
-                            "{}/post/{}",
-                            window.location().origin().unwrap(),
-                            _slug_for_async
-                        );
-
-                        let navigator = window.navigator();
-                        let clipboard = navigator.clipboard();
-                        let promise = clipboard.write_text(&full_url);
-                        let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
-                    }
-                }
+                copy_post_link(&_slug_for_async, None).await;
 
                 // Wait 2 seconds then reset
                 #[cfg(feature = "hydrate")]
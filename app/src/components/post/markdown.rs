@@ -1,8 +1,17 @@
 use super::code::CodeRunner;
+use super::diagram::{Diagram, DiagramKind};
+use super::images::{figure_html, image_html, ImageInfo};
+use super::math::{extract_math, render_placeholders, restore_markdown, restore_source, Formula};
+#[cfg(feature = "ssr")]
+use super::types::BlogPost;
+use super::video::{video_html, Video, VideoPlayer, VideoSource};
+use crate::components::ui::callout::{Callout, CalloutKind};
 use leptos::*;
 use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
-fn resolve_asset_url(url: &str, base_path: Option<&str>) -> String {
+pub(crate) fn resolve_asset_url(url: &str, base_path: Option<&str>) -> String {
     // If URL is already absolute (starts with http, https, or /), return as-is
     if url.starts_with("http://") || url.starts_with("https://") || url.starts_with("/") {
         return url.to_string();
@@ -18,10 +27,283 @@ fn resolve_asset_url(url: &str, base_path: Option<&str>) -> String {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum MarkdownElement {
     Html(String),
-    CodeBlock { code: String, language: String },
+    CodeBlock {
+        code: String,
+        language: String,
+    },
+    // `svg` is filled in on the server, see `render::render_post`
+    Diagram {
+        kind: DiagramKind,
+        source: String,
+        svg: Option<String>,
+    },
+    Video(Video),
+    // GitHub-style `> [!NOTE]` blockquote; `title` is any text after the marker
+    Callout {
+        kind: CalloutKind,
+        title: Option<String>,
+        elements: Vec<MarkdownElement>,
+    },
+}
+
+// A heading and the headings nested under it, for the table of contents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: u32,
+    pub id: String,
+    pub text: String,
+    pub children: Vec<Heading>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ParsedMarkdown {
+    pub elements: Vec<MarkdownElement>,
+    pub headings: Vec<Heading>,
+    // Every id the renderer gave an element (headings, figures, footnotes), so the sanitizer
+    // can tell them from ids in raw HTML; only needed on the server
+    #[serde(skip)]
+    pub ids: HashSet<String>,
+}
+
+// Lowercase words joined by dashes, e.g. "Hello, World!" -> "hello-world"
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if matches!(c, ' ' | '-' | '_') && !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+// Slug for a heading, suffixed with -1, -2, ... when the id is already taken by an earlier
+// heading or an explicit `{#id}`. Generated heading ids never start with `fig-`, which is
+// left to figures, so a heading can't take a figure's id whichever comes first
+fn unique_heading_id(taken: &mut HashSet<String>, text: &str) -> String {
+    let base = match slugify(text) {
+        slug if slug.is_empty() => "section".to_string(),
+        slug if slug.starts_with("fig-") => format!("section-{}", slug),
+        slug => slug,
+    };
+    unique_id(taken, base)
+}
+
+// `fig-` and the slug of a figure's label, suffixed when an explicit `{#id}` or an earlier
+// figure has it
+fn unique_figure_id(taken: &mut HashSet<String>, label: &str) -> String {
+    unique_id(taken, format!("fig-{}", slugify(label)))
+}
+
+fn unique_id(taken: &mut HashSet<String>, base: String) -> String {
+    if taken.insert(base.clone()) {
+        return base;
+    }
+    let mut n = 1;
+    loop {
+        let id = format!("{}-{}", base, n);
+        if taken.insert(id.clone()) {
+            return id;
+        }
+        n += 1;
+    }
+}
+
+// Ids given with `{#id}`, reserved before any are generated so a later heading or figure
+// can't take one
+fn explicit_heading_ids(source: &[Event<'_>]) -> HashSet<String> {
+    source
+        .iter()
+        .filter_map(|event| match event {
+            Event::End(Tag::Heading(_, Some(id), _)) => Some((*id).to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+// Nest a flat list of headings under the nearest preceding heading of a higher level
+fn heading_tree(flat: Vec<Heading>) -> Vec<Heading> {
+    let mut roots: Vec<Heading> = Vec::new();
+    let mut stack: Vec<Heading> = Vec::new();
+
+    for heading in flat {
+        while stack.last().is_some_and(|open| open.level >= heading.level) {
+            let done = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(done),
+                None => roots.push(done),
+            }
+        }
+        stack.push(heading);
+    }
+    while let Some(done) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(done),
+            None => roots.push(done),
+        }
+    }
+
+    roots
+}
+
+// Recognizes `[!NOTE]` and friends as the first line of a blockquote, given the events after
+// its start. Returns the kind, an optional title, how many events the marker line spans and
+// whether the paragraph it opened continues past it.
+fn callout_marker(events: &[Event<'_>]) -> Option<(CalloutKind, Option<String>, usize, bool)> {
+    if !matches!(events.first(), Some(Event::Start(Tag::Paragraph))) {
+        return None;
+    }
+
+    let mut line = String::new();
+    let mut end = 1;
+    while let Some(Event::Text(text)) = events.get(end) {
+        line.push_str(text);
+        end += 1;
+    }
+    let keep_paragraph = match events.get(end) {
+        Some(Event::SoftBreak | Event::HardBreak) => true,
+        Some(Event::End(Tag::Paragraph)) => false,
+        _ => return None,
+    };
+
+    let (name, title) = line.trim().strip_prefix("[!")?.split_once(']')?;
+    let kind = CalloutKind::from_name(name)?;
+    let title = Some(title.trim().to_string()).filter(|title| !title.is_empty());
+    Some((kind, title, end + 1, keep_paragraph))
+}
+
+// An image being read; its alt text is made up of the events up to its end
+struct OpenImage {
+    url: String,
+    title: String,
+    alt: String,
+    // Set when the link is a video file or a YouTube/Vimeo page
+    video: Option<VideoSource>,
+}
+
+// A numbered, captioned image; `@fig:label` in the text links to it
+struct Figure {
+    number: usize,
+    id: String,
+    label: String,
+}
+
+// `{#fig:label}` written right after an image, and whatever text follows it
+fn figure_label_marker(text: &str) -> Option<(&str, &str)> {
+    let (label, rest) = text.strip_prefix("{#fig:")?.split_once('}')?;
+    let valid = !label.is_empty()
+        && label
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    valid.then_some((label, rest))
+}
+
+// First pass over the events: which images are figures, in order, so that references
+// can point at figures further down. Images with a title or an explicit label are figures
+// and are labelled by their alt text unless given a label.
+// Figure ids are taken from `ids`, the ids used in the whole document.
+fn number_figures(
+    source: &[Event<'_>],
+    formulas: &[Formula],
+    ids: &mut HashSet<String>,
+) -> Vec<Option<Figure>> {
+    let mut figures = Vec::new();
+    // Whether the open image has a title, and its alt text so far
+    let mut open: Option<(bool, String)> = None;
+
+    for (index, event) in source.iter().enumerate() {
+        match event {
+            Event::Start(Tag::Image(_, url, title))
+                if VideoSource::from_url(url, url).is_none() =>
+            {
+                open = Some((!title.is_empty(), String::new()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, alt)) = open.as_mut() {
+                    alt.push_str(&restore_source(text, formulas));
+                }
+            }
+            Event::End(Tag::Image(..)) => {
+                let Some((titled, alt)) = open.take() else {
+                    continue;
+                };
+                let explicit = match source.get(index + 1) {
+                    Some(Event::Text(text)) => figure_label_marker(text).map(|(label, _)| label),
+                    _ => None,
+                };
+                if !titled && explicit.is_none() {
+                    figures.push(None);
+                    continue;
+                }
+
+                let number = figures.iter().flatten().count() + 1;
+                let label = match explicit {
+                    Some(label) => label.to_string(),
+                    None => match slugify(&alt) {
+                        label if label.is_empty() => number.to_string(),
+                        label => label,
+                    },
+                };
+                figures.push(Some(Figure {
+                    number,
+                    id: unique_figure_id(ids, &label),
+                    label,
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    figures
+}
+
+// Replace `@fig:label` references in rendered text with links to the figure
+fn link_figure_refs(html: &str, figures: &HashMap<&str, &Figure>) -> Option<String> {
+    let mut output = String::new();
+    let mut rest = html;
+    let mut linked = false;
+
+    while let Some(start) = rest.find("@fig:") {
+        let after = &rest[start + "@fig:".len()..];
+        let end = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(after.len());
+        // Sentence punctuation after a label isn't part of it
+        let label = after[..end].trim_end_matches(['-', '_']);
+
+        output.push_str(&rest[..start]);
+        match figures.get(label) {
+            Some(figure) => {
+                output.push_str(&format!(
+                    r##"<a href="#{}">Figure {}</a>"##,
+                    figure.id, figure.number
+                ));
+                linked = true;
+            }
+            // Unknown labels are left as written so they stand out
+            None => output.push_str(&rest[start..start + "@fig:".len() + label.len()]),
+        }
+        rest = &after[label.len()..];
+    }
+    output.push_str(rest);
+
+    linked.then_some(output)
+}
+
+// Render the pending events into an Html element
+fn flush_html(events: &mut Vec<Event<'_>>, elements: &mut Vec<MarkdownElement>) {
+    if events.is_empty() {
+        return;
+    }
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, events.drain(..));
+    if !html_output.trim().is_empty() {
+        elements.push(MarkdownElement::Html(html_output));
+    }
 }
 
 pub fn parse_markdown_elements(content: &str) -> Vec<MarkdownElement> {
@@ -32,12 +314,11 @@ pub fn parse_markdown_elements_with_base(
     content: &str,
     base_path: Option<&str>,
 ) -> Vec<MarkdownElement> {
-    if content.trim().is_empty() {
-        return vec![MarkdownElement::Html(
-            "<p>No content available.</p>".to_string(),
-        )];
-    }
+    parse_markdown(content, base_path, &HashMap::new()).elements
+}
 
+// Extensions enabled for post content
+pub(crate) fn markdown_options() -> Options {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TABLES);
@@ -45,31 +326,196 @@ pub fn parse_markdown_elements_with_base(
     options.insert(Options::ENABLE_TASKLISTS);
     options.insert(Options::ENABLE_SMART_PUNCTUATION);
     options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+    options
+}
+
+// `images` holds the server-side image manifest, keyed by resolved URL
+pub fn parse_markdown<S: std::hash::BuildHasher>(
+    content: &str,
+    base_path: Option<&str>,
+    images: &HashMap<String, ImageInfo, S>,
+) -> ParsedMarkdown {
+    if content.trim().is_empty() {
+        return ParsedMarkdown {
+            elements: vec![MarkdownElement::Html(
+                "<p>No content available.</p>".to_string(),
+            )],
+            headings: Vec::new(),
+            ids: HashSet::new(),
+        };
+    }
 
-    let parser = Parser::new_ext(content, options);
+    // Formulas are swapped for placeholders first so Markdown syntax inside them is left alone
+    let (content, formulas) = extract_math(content);
+
+    // Collected up front so callout markers can be looked ahead at
+    let source: Vec<Event<'_>> = Parser::new_ext(&content, markdown_options()).collect();
+    // One set of ids for headings and figures, so neither can take an explicit `{#id}`
+    let mut ids = explicit_heading_ids(&source);
+    let figures = number_figures(&source, &formulas, &mut ids);
+    let mut figure_refs: HashMap<&str, &Figure> = HashMap::new();
+    for figure in figures.iter().flatten() {
+        figure_refs.entry(figure.label.as_str()).or_insert(figure);
+    }
+    let mut image_count = 0;
     let mut elements = Vec::new();
     let mut current_html = String::new();
     let mut events = Vec::new();
     let mut code_block_lang = String::new();
     let mut code_block_content = String::new();
     let mut in_code_block = false;
+    let mut in_indented_code = false;
+    let mut headings = Vec::new();
+    // Position of the open heading's start event in `events`, and its text so far
+    let mut open_heading: Option<(usize, String)> = None;
+    let mut open_image: Option<OpenImage> = None;
+    // Whether the last paragraph opened is still open, for block elements found inside one
+    let mut in_paragraph = false;
+    // Open blockquotes, innermost last: callouts with the index of their first element
+    let mut blockquotes: Vec<Option<(CalloutKind, Option<String>, usize)>> = Vec::new();
+    let mut skip = 0;
+
+    for (index, event) in source.iter().enumerate() {
+        if skip > 0 {
+            skip -= 1;
+            continue;
+        }
+        let event = event.clone();
+
+        match &event {
+            Event::Start(Tag::Paragraph) => in_paragraph = true,
+            Event::End(Tag::Paragraph) => in_paragraph = false,
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)) => in_indented_code = true,
+            Event::End(Tag::CodeBlock(CodeBlockKind::Indented)) => in_indented_code = false,
+            _ => {}
+        }
+
+        // An image's inner events only make up its alt text
+        if let Some(image) = open_image.as_mut() {
+            match &event {
+                Event::End(Tag::Image(..)) => {}
+                Event::Text(text) | Event::Code(text) => {
+                    image.alt.push_str(&restore_source(text, &formulas));
+                    continue;
+                }
+                _ => continue,
+            }
+        }
+
+        if let (Some((_, heading_text)), Event::Text(text) | Event::Code(text)) =
+            (open_heading.as_mut(), &event)
+        {
+            heading_text.push_str(&restore_source(text, &formulas));
+        }
+
+        let event = match event {
+            // extract_math leaves code alone, but never render a stray placeholder inside it
+            Event::Text(text) if in_code_block || in_indented_code => {
+                Event::Text(CowStr::from(restore_markdown(&text, &formulas)))
+            }
+            Event::Code(text) => Event::Code(CowStr::from(restore_markdown(&text, &formulas))),
+            Event::Text(text) => {
+                let html = render_placeholders(&text, &formulas);
+                let linked = if text.contains("@fig:") {
+                    let escaped = html
+                        .clone()
+                        .unwrap_or_else(|| html_escape::encode_text(&text).into_owned());
+                    link_figure_refs(&escaped, &figure_refs)
+                } else {
+                    None
+                };
+                match linked.or(html) {
+                    Some(html) => Event::Html(CowStr::from(html)),
+                    None => Event::Text(text),
+                }
+            }
+            event => event,
+        };
 
-    for event in parser {
         match &event {
+            Event::Start(Tag::Heading(..)) => {
+                open_heading = Some((events.len(), String::new()));
+            }
+            // pulldown-cmark uses the label as the definition's id
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                ids.insert(label.to_string());
+            }
+            Event::End(Tag::Heading(level, id, classes)) => {
+                if let Some((start, text)) = open_heading.take() {
+                    // `{#custom-id}` wins; otherwise derive one, numbering repeats
+                    let id = id.map_or_else(
+                        || unique_heading_id(&mut ids, &text),
+                        |id| (*id).to_string(),
+                    );
+
+                    let class = if classes.is_empty() {
+                        String::new()
+                    } else {
+                        format!(
+                            r#" class="{}""#,
+                            html_escape::encode_double_quoted_attribute(&classes.join(" "))
+                        )
+                    };
+                    let level = *level as u32;
+                    if start < events.len() {
+                        events[start] = Event::Html(CowStr::from(format!(
+                            r#"<h{} id="{}"{}>"#,
+                            level,
+                            html_escape::encode_double_quoted_attribute(&id),
+                            class
+                        )));
+                    }
+
+                    // Copies a link to the section when clicked, see `use_section_links`
+                    events.push(Event::Html(CowStr::from(format!(
+                        r##"<a class="heading-anchor" href="#{}" aria-label="Copy link to this section">#</a>"##,
+                        html_escape::encode_double_quoted_attribute(&id)
+                    ))));
+
+                    headings.push(Heading {
+                        level,
+                        id,
+                        text: text.trim().to_string(),
+                        children: Vec::new(),
+                    });
+                }
+            }
             Event::Html(html) => {
                 // Pass HTML through directly, preserving entities
                 events.push(Event::Html(html.clone()));
                 continue;
             }
-            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang))) => {
-                // Flush any accumulated HTML
-                if !current_html.is_empty() || !events.is_empty() {
-                    html::push_html(&mut current_html, events.drain(..));
-                    if !current_html.trim().is_empty() {
-                        elements.push(MarkdownElement::Html(current_html.clone()));
+            Event::Start(Tag::BlockQuote) => {
+                if let Some((kind, title, marker_len, keep_paragraph)) =
+                    callout_marker(&source[index + 1..])
+                {
+                    // The callout's content becomes its own list of elements
+                    flush_html(&mut events, &mut elements);
+                    blockquotes.push(Some((kind, title, elements.len())));
+                    skip = marker_len;
+                    if keep_paragraph {
+                        events.push(Event::Start(Tag::Paragraph));
                     }
-                    current_html.clear();
+                    in_paragraph = keep_paragraph;
+                    continue;
+                }
+                blockquotes.push(None);
+            }
+            Event::End(Tag::BlockQuote) => {
+                if let Some(Some((kind, title, start))) = blockquotes.pop() {
+                    flush_html(&mut events, &mut elements);
+                    let inner = elements.split_off(start);
+                    elements.push(MarkdownElement::Callout {
+                        kind,
+                        title,
+                        elements: inner,
+                    });
+                    continue;
                 }
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang))) => {
+                // Flush any accumulated HTML
+                flush_html(&mut events, &mut elements);
 
                 in_code_block = true;
                 code_block_lang = lang.to_string();
@@ -84,41 +530,121 @@ pub fn parse_markdown_elements_with_base(
                 in_code_block = false;
 
                 // Add code block as separate element
-                elements.push(MarkdownElement::CodeBlock {
-                    code: code_block_content.clone(),
-                    language: code_block_lang.clone(),
+                elements.push(match DiagramKind::from_language(&code_block_lang) {
+                    Some(kind) => MarkdownElement::Diagram {
+                        kind,
+                        source: code_block_content.clone(),
+                        svg: None,
+                    },
+                    None => MarkdownElement::CodeBlock {
+                        code: code_block_content.clone(),
+                        language: code_block_lang.clone(),
+                    },
                 });
                 continue;
             }
-            // Handle images and videos
-            Event::Start(Tag::Image(link_type, url, title)) => {
+            // Images and videos are written out once their alt text has been read
+            Event::Start(Tag::Image(_, url, title)) => {
                 let resolved_url = resolve_asset_url(url, base_path);
+                open_image = Some(OpenImage {
+                    video: VideoSource::from_url(url, &resolved_url),
+                    url: resolved_url,
+                    title: title.to_string(),
+                    alt: String::new(),
+                });
+                continue;
+            }
+            Event::End(Tag::Image(..)) => {
+                if let Some(OpenImage {
+                    url,
+                    title,
+                    alt,
+                    video,
+                }) = open_image.take()
+                {
+                    if let Some(video_source) = video {
+                        let title = restore_source(&title, &formulas);
+                        let video = Video::new(video_source, &alt, &title);
+
+                        // Videos are elements of their own, so a paragraph around one is
+                        // closed before it and reopened after it if anything follows
+                        if in_paragraph {
+                            if matches!(events.last(), Some(Event::Start(Tag::Paragraph))) {
+                                events.pop();
+                            } else {
+                                events.push(Event::End(Tag::Paragraph));
+                            }
+                        }
+                        flush_html(&mut events, &mut elements);
+                        elements.push(MarkdownElement::Video(video));
+                        if in_paragraph {
+                            if matches!(source.get(index + 1), Some(Event::End(Tag::Paragraph))) {
+                                skip = 1;
+                                in_paragraph = false;
+                            } else {
+                                events.push(Event::Start(Tag::Paragraph));
+                            }
+                        }
+                        continue;
+                    }
+
+                    let figure = figures.get(image_count).and_then(Option::as_ref);
+                    image_count += 1;
 
-                // Convert image tags with .mp4/.webm extensions to video tags
-                if url.ends_with(".mp4") || url.ends_with(".webm") || url.ends_with(".mov") {
-                    let video_html = format!(
-                        r#"<video controls class="w-full my-8 border-2 border-border">
-                            <source src="{}" type="video/{}">
-                            Your browser does not support the video tag.
-                        </video>"#,
-                        resolved_url,
-                        if url.ends_with(".mp4") {
-                            "mp4"
-                        } else if url.ends_with(".webm") {
-                            "webm"
+                    let Some(figure) = figure else {
+                        let title = restore_source(&title, &formulas);
+                        events.push(Event::Html(CowStr::from(image_html(
+                            &url,
+                            alt.trim(),
+                            &title,
+                            images.get(&url),
+                        ))));
+                        continue;
+                    };
+
+                    // The caption replaces the title tooltip
+                    let image = image_html(&url, alt.trim(), "", images.get(&url));
+                    let caption = render_placeholders(&title, &formulas)
+                        .unwrap_or_else(|| html_escape::encode_text(&title).into_owned());
+
+                    // Drop an explicit `{#fig:label}`, keeping any text after it
+                    let mut next = index + 1;
+                    let mut trailing = None;
+                    if let Some(Event::Text(text)) = source.get(next) {
+                        if let Some((_, rest)) = figure_label_marker(text) {
+                            skip = 1;
+                            next += 1;
+                            trailing =
+                                Some(rest.trim_start().to_string()).filter(|rest| !rest.is_empty());
+                        }
+                    }
+
+                    // A figure can't sit inside a paragraph: one that opens a paragraph goes
+                    // before it, and replaces it entirely when nothing else follows
+                    let opens_paragraph =
+                        matches!(events.last(), Some(Event::Start(Tag::Paragraph)));
+                    if opens_paragraph {
+                        events.pop();
+                    }
+                    events.push(Event::Html(CowStr::from(figure_html(
+                        &figure.id,
+                        figure.number,
+                        &caption,
+                        &image,
+                    ))));
+                    if opens_paragraph {
+                        if trailing.is_none()
+                            && matches!(source.get(next), Some(Event::End(Tag::Paragraph)))
+                        {
+                            skip += 1;
+                            in_paragraph = false;
                         } else {
-                            "quicktime"
+                            events.push(Event::Start(Tag::Paragraph));
                         }
-                    );
-                    events.push(Event::Html(CowStr::from(video_html)));
-                    continue;
-                } else {
-                    // Handle regular images with resolved URLs
-                    events.push(Event::Start(Tag::Image(
-                        link_type.clone(),
-                        CowStr::from(resolved_url),
-                        title.clone(),
-                    )));
+                    }
+                    if let Some(rest) = trailing {
+                        events.push(Event::Text(CowStr::from(rest)));
+                    }
                     continue;
                 }
             }
@@ -131,23 +657,33 @@ pub fn parse_markdown_elements_with_base(
     if !current_html.is_empty() || !events.is_empty() {
         html::push_html(&mut current_html, events.into_iter());
         if !current_html.trim().is_empty() {
-            // Process images
-            current_html = current_html.replace(
-                "<img ",
-                r#"<img class="w-full border-2 border-border my-8" "#,
-            );
             elements.push(MarkdownElement::Html(current_html));
         }
     }
 
-    elements
+    ParsedMarkdown {
+        elements,
+        headings: heading_tree(headings),
+        ids,
+    }
 }
 
 // Keep the old function for compatibility but make it use the new system
 pub fn render_markdown(content: &str) -> String {
-    let elements = parse_markdown_elements(content);
     let mut html_output = String::new();
+    push_elements_html(parse_markdown_elements(content), &mut html_output);
+    html_output
+}
+
+// A post's content as HTML, from the same cached render the post page uses
+#[cfg(feature = "ssr")]
+pub fn render_post_markdown(post: &BlogPost) -> String {
+    let mut html_output = String::new();
+    push_elements_html(super::render::render_post(post).elements, &mut html_output);
+    html_output
+}
 
+fn push_elements_html(elements: Vec<MarkdownElement>, html_output: &mut String) {
     for element in elements {
         match element {
             MarkdownElement::Html(html) => {
@@ -161,10 +697,34 @@ pub fn render_markdown(content: &str) -> String {
                     html_escape::encode_text(&code)
                 ));
             }
+            MarkdownElement::Diagram { kind, source, svg } => {
+                #[cfg(feature = "ssr")]
+                let svg = svg.or_else(|| super::diagram::render_diagram(kind, &source));
+
+                match svg {
+                    Some(svg) => html_output.push_str(&format!("<figure>{}</figure>", svg)),
+                    None => html_output.push_str(&format!(
+                        r#"<pre><code class="language-{}">{}</code></pre>"#,
+                        kind.language(),
+                        html_escape::encode_text(&source)
+                    )),
+                }
+            }
+            MarkdownElement::Video(video) => html_output.push_str(&video_html(&video)),
+            MarkdownElement::Callout {
+                kind,
+                title,
+                elements,
+            } => {
+                html_output.push_str(&format!(
+                    "<blockquote><p><strong>{}</strong></p>",
+                    html_escape::encode_text(title.as_deref().unwrap_or_else(|| kind.label()))
+                ));
+                push_elements_html(elements, html_output);
+                html_output.push_str("</blockquote>");
+            }
         }
     }
-
-    html_output
 }
 
 #[component]
@@ -179,43 +739,254 @@ pub fn Markdown(content: String, #[prop(optional)] base_path: Option<String>) ->
         }
     });
 
+    move || view! { <MarkdownElements elements=elements.get()/> }
+}
+
+// Renders already-parsed elements, for callers that also need the parse result
+#[component]
+pub fn MarkdownElements(elements: Vec<MarkdownElement>) -> impl IntoView {
     view! {
         <div class="prose text-foreground max-w-none text-sm sm:text-base md:text-lg leading-relaxed">
-            {move || elements.get().into_iter().enumerate().map(|(_i, element)| {
-                match element {
-                    MarkdownElement::Html(html) => {
-                        // Check if this contains ASCII art or is a pre tag with ASCII art
-                        let is_ascii_art = html.contains("██") || html.contains("╗") || html.contains("╔") || html.contains("╚") || html.contains("═");
-
-                        if is_ascii_art {
-                            // If it's already wrapped in a pre tag, add transparent background styles
-                            let styled_html = if html.contains("<pre>") {
-                                html.replace("<pre>", r#"<pre style="background: transparent !important; border: none !important; padding: 0 !important;">"#)
-                            } else {
-                                // Replace <br> tags with actual newlines for proper display in pre tag
-                                let formatted = html.replace("<br>", "\n").replace("<br/>", "\n").replace("<br />", "\n");
-                                format!(r#"<pre style="background: transparent !important; border: none !important; padding: 0 !important;">{}</pre>"#, formatted)
-                            };
-
-                            view! {
-                                <div class="flex justify-center font-mono" inner_html=styled_html></div>
-                            }.into_view()
-                        } else {
-                            view! {
-                                <div inner_html=html></div>
-                            }.into_view()
-                        }
-                    },
-                    MarkdownElement::CodeBlock { code, language } => view! {
-                        <CodeRunner
-                            code=code
-                            language=language
-                            show_copy=true
-                            show_run=true
-                        />
-                    }.into_view(),
-                }
-            }).collect_view()}
+            {elements_view(elements)}
         </div>
     }
 }
+
+fn elements_view(elements: Vec<MarkdownElement>) -> View {
+    elements.into_iter().enumerate().map(|(_i, element)| {
+        match element {
+            MarkdownElement::Html(html) => {
+                // Check if this contains ASCII art or is a pre tag with ASCII art
+                let is_ascii_art = html.contains("██") || html.contains("╗") || html.contains("╔") || html.contains("╚") || html.contains("═");
+
+                if is_ascii_art {
+                    // If it's already wrapped in a pre tag, add transparent background styles
+                    let styled_html = if html.contains("<pre>") {
+                        html.replace("<pre>", r#"<pre style="background: transparent !important; border: none !important; padding: 0 !important;">"#)
+                    } else {
+                        // Replace <br> tags with actual newlines for proper display in pre tag
+                        let formatted = html.replace("<br>", "\n").replace("<br/>", "\n").replace("<br />", "\n");
+                        format!(r#"<pre style="background: transparent !important; border: none !important; padding: 0 !important;">{}</pre>"#, formatted)
+                    };
+
+                    view! {
+                        <div class="flex justify-center font-mono" inner_html=styled_html></div>
+                    }.into_view()
+                } else {
+                    view! {
+                        <div inner_html=html></div>
+                    }.into_view()
+                }
+            },
+            MarkdownElement::CodeBlock { code, language } => view! {
+                <CodeRunner
+                    code=code
+                    language=language
+                    show_copy=true
+                    show_run=true
+                />
+            }.into_view(),
+            MarkdownElement::Diagram { kind, source, svg } => view! {
+                <Diagram kind=kind source=source svg=svg/>
+            }.into_view(),
+            MarkdownElement::Video(video) => view! { <VideoPlayer video=video/> }.into_view(),
+            MarkdownElement::Callout { kind, title, elements } => view! {
+                <Callout kind=kind title=title.unwrap_or_else(|| kind.label().to_string())>
+                    {elements_view(elements)}
+                </Callout>
+            }.into_view(),
+        }
+    }).collect_view()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> ParsedMarkdown {
+        parse_markdown(content, None, &HashMap::new())
+    }
+
+    fn html(content: &str) -> String {
+        let mut output = String::new();
+        push_elements_html(parse(content).elements, &mut output);
+        output
+    }
+
+    fn heading_ids(headings: &[Heading]) -> Vec<String> {
+        headings
+            .iter()
+            .flat_map(|heading| {
+                std::iter::once(heading.id.clone()).chain(heading_ids(&heading.children))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn math_in_quoted_and_indented_code_stays_source() {
+        let output = html("> ```bash\n> echo $HOME/$USER\n> ```\n\n    cost = $a$\n\nInline $b$");
+        assert!(output.contains("echo $HOME/$USER"));
+        assert!(output.contains("cost = $a$"));
+        assert_eq!(output.matches("<math").count(), 1);
+    }
+
+    #[test]
+    fn slugify_keeps_words_and_drops_punctuation() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("  Leading and trailing  "), "leading-and-trailing");
+        assert_eq!(slugify("snake_case -- and dashes"), "snake-case-and-dashes");
+        assert_eq!(slugify("Ünïcödé Straße"), "ünïcödé-straße");
+        assert_eq!(slugify("C++ & Rust?"), "c-rust");
+        assert_eq!(slugify("!!!"), "");
+    }
+
+    #[test]
+    fn repeated_headings_are_numbered() {
+        let mut taken = HashSet::new();
+        assert_eq!(unique_heading_id(&mut taken, "Setup"), "setup");
+        assert_eq!(unique_heading_id(&mut taken, "Setup"), "setup-1");
+        assert_eq!(unique_heading_id(&mut taken, "Setup"), "setup-2");
+        assert_eq!(unique_heading_id(&mut taken, "?"), "section");
+        assert_eq!(unique_heading_id(&mut taken, "!"), "section-1");
+    }
+
+    #[test]
+    fn generated_ids_skip_taken_ones() {
+        // "setup-1" is already used, so the second "Setup" moves on to "setup-2"
+        let mut taken = HashSet::from(["setup-1".to_string()]);
+        assert_eq!(unique_heading_id(&mut taken, "Setup"), "setup");
+        assert_eq!(unique_heading_id(&mut taken, "Setup"), "setup-2");
+        assert_eq!(unique_heading_id(&mut taken, "Setup 1"), "setup-1-1");
+    }
+
+    #[test]
+    fn explicit_ids_are_never_generated_again() {
+        let parsed = parse("## Intro\n\n## Intro\n\n## Later {#intro-1}\n\n## Usage {#intro}\n");
+        assert_eq!(
+            heading_ids(&parsed.headings),
+            ["intro-2", "intro-3", "intro-1", "intro"]
+        );
+    }
+
+    #[test]
+    fn figure_labels_are_read_from_the_marker() {
+        assert_eq!(
+            figure_label_marker("{#fig:arch} and more"),
+            Some(("arch", " and more"))
+        );
+        assert_eq!(figure_label_marker("{#fig:a_b-2}"), Some(("a_b-2", "")));
+        assert_eq!(figure_label_marker("{#fig:}"), None);
+        assert_eq!(figure_label_marker("{#fig:has space}"), None);
+        assert_eq!(figure_label_marker("{#fig:open"), None);
+        assert_eq!(figure_label_marker("text {#fig:late}"), None);
+    }
+
+    #[test]
+    fn figures_are_numbered_and_referenced() {
+        let output = html(
+            "See @fig:chart.\n\n![Chart](/a.png \"Sales\")\n\n![Plain](/b.png)\n\n\
+             ![Other](/c.png){#fig:second}\n\nAnd @fig:second, not @fig:missing.\n",
+        );
+        assert!(output.contains(r##"<a href="#fig-chart">Figure 1</a>"##));
+        assert!(output.contains(r##"<a href="#fig-second">Figure 2</a>"##));
+        assert!(output.contains("@fig:missing"));
+        assert!(!output.contains("{#fig:second}"));
+    }
+
+    #[test]
+    fn headings_never_take_figure_ids() {
+        let parsed = parse("## Fig chart\n\n![Chart](/a.png \"Sales\")\n\n## Fig chart\n");
+        assert_eq!(
+            heading_ids(&parsed.headings),
+            ["section-fig-chart", "section-fig-chart-1"]
+        );
+        let mut output = String::new();
+        push_elements_html(parsed.elements, &mut output);
+        assert_eq!(output.matches(r#"id="fig-chart""#).count(), 1);
+        assert!(output.contains(r#"<figure id="fig-chart""#));
+
+        // An explicit heading id wins over a figure's generated one
+        let parsed = parse("![Chart](/a.png \"Sales\")\n\n## Summary {#fig-chart}\n");
+        let mut output = String::new();
+        push_elements_html(parsed.elements, &mut output);
+        assert_eq!(output.matches(r#"id="fig-chart""#).count(), 1);
+        assert!(output.contains(r#"id="fig-chart-1""#));
+    }
+
+    #[test]
+    fn headings_nest_under_the_previous_higher_level() {
+        let parsed = parse("# A\n\n## B\n\n### C\n\n## D\n\n# E\n");
+        let tree: Vec<(String, Vec<String>)> = parsed
+            .headings
+            .iter()
+            .map(|h| (h.id.clone(), heading_ids(&h.children)))
+            .collect();
+        assert_eq!(
+            tree,
+            [
+                (
+                    "a".to_string(),
+                    vec!["b".to_string(), "c".to_string(), "d".to_string()]
+                ),
+                ("e".to_string(), Vec::new()),
+            ]
+        );
+        assert!(html("## Hi there\n").contains(r#"<h2 id="hi-there">"#));
+    }
+
+    // Kind, title and whether the first paragraph goes on after the marker line
+    fn marker(content: &str) -> Option<(CalloutKind, Option<String>, bool)> {
+        let events: Vec<Event<'_>> = Parser::new_ext(content, markdown_options()).collect();
+        callout_marker(&events[1..]).map(|(kind, title, _, keep)| (kind, title, keep))
+    }
+
+    #[test]
+    fn callout_markers_are_recognized() {
+        assert_eq!(
+            marker("> [!NOTE]\n> Body"),
+            Some((CalloutKind::Note, None, true))
+        );
+        assert_eq!(
+            marker("> [!warning] Breaking change\n"),
+            Some((
+                CalloutKind::Warning,
+                Some("Breaking change".to_string()),
+                false
+            ))
+        );
+        assert_eq!(marker("> [!UNKNOWN]\n> Body"), None);
+        assert_eq!(marker("> Just a quote [!NOTE]"), None);
+        assert_eq!(marker("> [!NOTE] with *emphasis*"), None);
+    }
+
+    #[test]
+    fn callouts_keep_their_body_and_nest() {
+        let elements =
+            parse("> [!TIP] Try this\n> First line\n>\n> > [!CAUTION]\n> > Careful\n").elements;
+        let [MarkdownElement::Callout {
+            kind,
+            title,
+            elements,
+        }] = elements.as_slice()
+        else {
+            panic!("expected one callout, got {:?}", elements);
+        };
+        assert_eq!(*kind, CalloutKind::Tip);
+        assert_eq!(title.as_deref(), Some("Try this"));
+        assert!(matches!(
+            elements.first(),
+            Some(MarkdownElement::Html(html)) if html.contains("First line") && !html.contains("[!TIP]")
+        ));
+        assert!(elements.iter().any(|element| matches!(
+            element,
+            MarkdownElement::Callout {
+                kind: CalloutKind::Caution,
+                title: None,
+                ..
+            }
+        )));
+
+        let plain = html("> A plain quote\n");
+        assert!(plain.contains("<blockquote>") && plain.contains("A plain quote"));
+    }
+}
@@ -0,0 +1,1000 @@
+// LaTeX math to MathML, so formulas render natively in the browser with no client-side library.
+//
+// Covers the subset of LaTeX that shows up in posts: scripts, fractions, roots, fences,
+// accents, fonts, matrices/cases and the common symbol set. Anything unrecognised is
+// shown as an `<merror>` with its source rather than failing the whole post.
+
+use super::markdown::markdown_options;
+use pulldown_cmark::{Event, Tag};
+use std::ops::Range;
+
+const MATHML_NS: &str = "http://www.w3.org/1998/Math/MathML";
+
+// Deepest nesting of groups and commands the parser recurses into; deeper formulas are shown
+// as their source, like unknown commands, instead of overflowing the stack
+const MAX_NESTING: usize = 64;
+
+// Marks a formula pulled out of the Markdown source; these private-use characters
+// pass through pulldown-cmark as plain text
+const PLACEHOLDER_START: char = '\u{E000}';
+const PLACEHOLDER_END: char = '\u{E001}';
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    pub tex: String,
+    pub display: bool,
+}
+
+/// Render `tex` as a `<math>` element; `display` selects block layout.
+pub fn latex_to_mathml(tex: &str, display: bool) -> String {
+    let mut parser = Parser {
+        tokens: tokenize(tex),
+        pos: 0,
+        display,
+        depth: 0,
+        too_deep: false,
+    };
+
+    let mut body = String::new();
+    while parser.pos < parser.tokens.len() {
+        body.push_str(&parser.parse_row());
+        // Stray `}`, `&` or `\\` at the top level: skip it and keep going
+        parser.pos += 1;
+    }
+    if parser.too_deep {
+        body = format!("<merror><mtext>{}</mtext></merror>", escape(tex));
+    }
+
+    format!(
+        r#"<math xmlns="{}" display="{}"><semantics><mrow>{}</mrow><annotation encoding="application/x-tex">{}</annotation></semantics></math>"#,
+        MATHML_NS,
+        if display { "block" } else { "inline" },
+        body,
+        html_escape::encode_text(tex)
+    )
+}
+
+/// Swap `$...$` and `$$...$$` in Markdown source for placeholders, leaving code alone.
+///
+/// Follows Pandoc's rules so prices like "$5 and $10" stay text: an opening `$` must be
+/// followed by a non-space, and a closing `$` preceded by one and not followed by a digit.
+pub fn extract_math(content: &str) -> (String, Vec<Formula>) {
+    let mut output = String::with_capacity(content.len());
+    let mut formulas = Vec::new();
+    let mut copied = 0;
+
+    // Code is copied verbatim; everything between it is scanned
+    for code in code_ranges(content) {
+        output.push_str(&replace_math(&content[copied..code.start], &mut formulas));
+        output.push_str(&content[code.clone()]);
+        copied = code.end;
+    }
+    output.push_str(&replace_math(&content[copied..], &mut formulas));
+
+    (output, formulas)
+}
+
+// Byte ranges of code blocks and code spans as the Markdown parser sees them, so fences in
+// blockquotes and lists, indented blocks and longer fences are all recognised
+fn code_ranges(content: &str) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (event, range) in
+        pulldown_cmark::Parser::new_ext(content, markdown_options()).into_offset_iter()
+    {
+        if matches!(event, Event::Start(Tag::CodeBlock(_)) | Event::Code(_))
+            && ranges.last().map_or(true, |last| range.start >= last.end)
+        {
+            ranges.push(range);
+        }
+    }
+    ranges
+}
+
+fn replace_math(text: &str, formulas: &mut Vec<Formula>) -> String {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut output = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (offset, c) = chars[i];
+        match c {
+            // Escaped dollar stays literal (pulldown-cmark drops the backslash)
+            '\\' if chars.get(i + 1).map(|(_, n)| *n) == Some('$') => {
+                output.push_str("\\$");
+                i += 2;
+            }
+            // Inline code spans are copied verbatim up to the matching backtick run
+            '`' => {
+                let run = chars[i..].iter().take_while(|(_, c)| *c == '`').count();
+                let fence = "`".repeat(run);
+                let after = offset + run;
+                if let Some(end) = text[after..].find(&fence) {
+                    let end = after + end + run;
+                    output.push_str(&text[offset..end]);
+                    i = chars.partition_point(|(o, _)| *o < end);
+                } else {
+                    output.push_str(&fence);
+                    i += run;
+                }
+            }
+            '$' => {
+                let display = chars.get(i + 1).map(|(_, n)| *n) == Some('$');
+                let start = offset + if display { 2 } else { 1 };
+                if let Some((end, next)) = find_closing(text, start, display) {
+                    output.push(PLACEHOLDER_START);
+                    output.push_str(&formulas.len().to_string());
+                    output.push(PLACEHOLDER_END);
+                    formulas.push(Formula {
+                        tex: text[start..end].trim().to_string(),
+                        display,
+                    });
+                    i = chars.partition_point(|(o, _)| *o < next);
+                } else {
+                    output.push('$');
+                    i += 1;
+                }
+            }
+            c => {
+                output.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    output
+}
+
+// Byte range end of the formula body and the index just past its closing delimiter
+fn find_closing(text: &str, start: usize, display: bool) -> Option<(usize, usize)> {
+    let body = &text[start..];
+    if display {
+        let end = body.find("$$")?;
+        return (!body[..end].trim().is_empty()).then_some((start + end, start + end + 2));
+    }
+
+    // Inline math can't start with whitespace, run into a code span or span a blank line
+    if body.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut escaped = false;
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '`' => return None,
+            '\n' if body[i + 1..]
+                .trim_start_matches([' ', '\t'])
+                .starts_with('\n') =>
+            {
+                return None;
+            }
+            '$' => {
+                let before = body[..i].chars().last();
+                let after = body[i + 1..].chars().next();
+                if i > 0
+                    && !before.is_some_and(char::is_whitespace)
+                    && !after.is_some_and(|a| a.is_ascii_digit())
+                {
+                    return Some((start + i, start + i + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Replace placeholders in a text event with the rendered formulas, escaping the text around them.
+///
+/// Returns `None` when the text holds no formulas so callers can pass it through untouched.
+pub fn render_placeholders(text: &str, formulas: &[Formula]) -> Option<String> {
+    text.contains(PLACEHOLDER_START).then(|| {
+        replace_placeholders(text, formulas, escape, |formula| {
+            latex_to_mathml(&formula.tex, formula.display)
+        })
+    })
+}
+
+/// Text with placeholders swapped back to their LaTeX source, e.g. for heading ids.
+pub fn restore_source(text: &str, formulas: &[Formula]) -> String {
+    replace_placeholders(text, formulas, str::to_string, |formula| {
+        formula.tex.clone()
+    })
+}
+
+/// Text with placeholders swapped back to the Markdown they replaced, delimiters included.
+pub fn restore_markdown(text: &str, formulas: &[Formula]) -> String {
+    replace_placeholders(text, formulas, str::to_string, |formula| {
+        let delimiter = if formula.display { "$$" } else { "$" };
+        format!("{}{}{}", delimiter, formula.tex, delimiter)
+    })
+}
+
+fn replace_placeholders(
+    text: &str,
+    formulas: &[Formula],
+    plain: impl Fn(&str) -> String,
+    formula: impl Fn(&Formula) -> String,
+) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(PLACEHOLDER_START) {
+        output.push_str(&plain(&rest[..start]));
+        let after = &rest[start + PLACEHOLDER_START.len_utf8()..];
+        let Some(end) = after.find(PLACEHOLDER_END) else {
+            rest = after;
+            continue;
+        };
+        if let Some(found) = after[..end]
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| formulas.get(i))
+        {
+            output.push_str(&formula(found));
+        }
+        rest = &after[end + PLACEHOLDER_END.len_utf8()..];
+    }
+    output.push_str(&plain(rest));
+    output
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Command(String),
+    Open,
+    Close,
+    Sup,
+    Sub,
+    Align,
+    Space,
+    Char(char),
+}
+
+fn tokenize(tex: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = tex.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\\' => {
+                let mut name = String::new();
+                while let Some(&next) = chars.peek() {
+                    if !next.is_ascii_alphabetic() {
+                        break;
+                    }
+                    name.push(next);
+                    chars.next();
+                }
+                if name.is_empty() {
+                    if let Some(symbol) = chars.next() {
+                        name.push(symbol);
+                    }
+                }
+                Token::Command(name)
+            }
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '^' => Token::Sup,
+            '_' => Token::Sub,
+            '&' => Token::Align,
+            c if c.is_whitespace() => Token::Space,
+            c => Token::Char(c),
+        };
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    display: bool,
+    // Atoms currently being parsed, and whether that ever went past `MAX_NESTING`
+    depth: usize,
+    too_deep: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(&Token::Space) {
+            self.pos += 1;
+        }
+    }
+
+    fn at_row_end(&self) -> bool {
+        match self.peek() {
+            None | Some(Token::Close | Token::Align) => true,
+            Some(Token::Command(name)) => matches!(name.as_str(), "\\" | "right" | "end"),
+            _ => false,
+        }
+    }
+
+    // Atoms with their scripts until the end of the current group, cell or row
+    fn parse_row(&mut self) -> String {
+        let mut row = String::new();
+        loop {
+            self.skip_spaces();
+            if self.at_row_end() {
+                return row;
+            }
+            let Some((base, limits)) = self.parse_atom() else {
+                continue;
+            };
+            row.push_str(&self.parse_scripts(base, limits));
+        }
+    }
+
+    // `{...}` as an mrow, or a single atom
+    fn parse_argument(&mut self) -> String {
+        self.skip_spaces();
+        match self.peek() {
+            Some(Token::Open) => {
+                self.pos += 1;
+                let row = self.parse_row();
+                self.expect_close();
+                format!("<mrow>{}</mrow>", row)
+            }
+            // `x^23` is x squared followed by 3
+            Some(Token::Char(c)) if c.is_ascii_digit() => {
+                let digit = *c;
+                self.pos += 1;
+                format!("<mn>{}</mn>", digit)
+            }
+            _ if self.at_row_end() => String::new(),
+            _ => self.parse_atom().map(|(atom, _)| atom).unwrap_or_default(),
+        }
+    }
+
+    fn expect_close(&mut self) {
+        if self.peek() == Some(&Token::Close) {
+            self.pos += 1;
+        }
+    }
+
+    // Raw text of a `{...}` group, for \text and font commands
+    fn parse_text_argument(&mut self) -> String {
+        self.skip_spaces();
+        if self.peek() != Some(&Token::Open) {
+            return match self.tokens.get(self.pos).cloned() {
+                Some(Token::Char(c)) => {
+                    self.pos += 1;
+                    c.to_string()
+                }
+                _ => String::new(),
+            };
+        }
+        self.pos += 1;
+
+        let mut text = String::new();
+        let mut depth = 0;
+        while let Some(token) = self.tokens.get(self.pos).cloned() {
+            self.pos += 1;
+            match token {
+                Token::Open => depth += 1,
+                Token::Close if depth == 0 => break,
+                Token::Close => depth -= 1,
+                Token::Space => text.push(' '),
+                Token::Char(c) => text.push(c),
+                Token::Sup => text.push('^'),
+                Token::Sub => text.push('_'),
+                Token::Align => text.push('&'),
+                Token::Command(name) if name.chars().count() == 1 => text.push_str(&name),
+                Token::Command(name) => {
+                    text.push_str(symbol(&name).map_or(name.as_str(), |(s, _)| s));
+                }
+            }
+        }
+        text
+    }
+
+    // Optional `[...]` argument, e.g. the index of \sqrt[3]{x}
+    fn parse_optional_argument(&mut self) -> Option<String> {
+        self.skip_spaces();
+        if self.peek() != Some(&Token::Char('[')) {
+            return None;
+        }
+        self.pos += 1;
+        let mut row = String::new();
+        while let Some(token) = self.peek() {
+            if *token == Token::Char(']') {
+                self.pos += 1;
+                break;
+            }
+            if let Some((atom, _)) = self.parse_atom() {
+                row.push_str(&atom);
+            }
+        }
+        Some(format!("<mrow>{}</mrow>", row))
+    }
+
+    fn parse_scripts(&mut self, base: String, limits: bool) -> String {
+        let mut sub = None;
+        let mut sup = None;
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some(Token::Sub) if sub.is_none() => {
+                    self.pos += 1;
+                    sub = Some(self.parse_argument());
+                }
+                Some(Token::Sup) if sup.is_none() => {
+                    self.pos += 1;
+                    sup = Some(self.parse_argument());
+                }
+                Some(Token::Char('\'')) if sup.is_none() => {
+                    self.pos += 1;
+                    sup = Some("<mo>′</mo>".to_string());
+                }
+                _ => break,
+            }
+        }
+
+        // Sums, limits and friends take their scripts above and below in display mode
+        let (under, over, both) = if limits && self.display {
+            ("munder", "mover", "munderover")
+        } else {
+            ("msub", "msup", "msubsup")
+        };
+        match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<{0}>{1}{2}</{0}>", under, base, sub),
+            (None, Some(sup)) => format!("<{0}>{1}{2}</{0}>", over, base, sup),
+            (Some(sub), Some(sup)) => format!("<{0}>{1}{2}{3}</{0}>", both, base, sub, sup),
+        }
+    }
+
+    // One atom and whether it takes limits; `None` if the token produced no output
+    fn parse_atom(&mut self) -> Option<(String, bool)> {
+        if self.depth >= MAX_NESTING {
+            // Skip everything left so every caller unwinds straight away
+            self.too_deep = true;
+            self.pos = self.tokens.len();
+            return None;
+        }
+        self.depth += 1;
+        let atom = self.parse_nested_atom();
+        self.depth -= 1;
+        atom
+    }
+
+    fn parse_nested_atom(&mut self) -> Option<(String, bool)> {
+        let token = self.tokens.get(self.pos).cloned()?;
+        self.pos += 1;
+
+        let atom = match token {
+            Token::Open => {
+                let row = self.parse_row();
+                self.expect_close();
+                format!("<mrow>{}</mrow>", row)
+            }
+            Token::Char(c) if c.is_ascii_digit() || c == '.' => {
+                let mut number = c.to_string();
+                while let Some(Token::Char(next)) = self.peek() {
+                    let next = *next;
+                    let decimal_point = next == '.'
+                        && matches!(self.tokens.get(self.pos + 1), Some(Token::Char(d)) if d.is_ascii_digit());
+                    if !(next.is_ascii_digit() || decimal_point) {
+                        break;
+                    }
+                    number.push(next);
+                    self.pos += 1;
+                }
+                if number == "." {
+                    "<mo>.</mo>".to_string()
+                } else {
+                    format!("<mn>{}</mn>", number)
+                }
+            }
+            Token::Char(c) if c.is_alphabetic() => format!("<mi>{}</mi>", c),
+            Token::Char(c) => format!("<mo>{}</mo>", operator(c)),
+            Token::Command(name) => return self.parse_command(&name),
+            Token::Space | Token::Close | Token::Align | Token::Sup | Token::Sub => return None,
+        };
+        Some((atom, false))
+    }
+
+    fn parse_command(&mut self, name: &str) -> Option<(String, bool)> {
+        let atom = match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+                format!("<mfrac>{}{}</mfrac>", numerator, denominator)
+            }
+            "binom" => {
+                let top = self.parse_argument();
+                let bottom = self.parse_argument();
+                format!(
+                    r#"<mrow><mo>(</mo><mfrac linethickness="0">{}{}</mfrac><mo>)</mo></mrow>"#,
+                    top, bottom
+                )
+            }
+            "sqrt" => match self.parse_optional_argument() {
+                Some(index) => format!("<mroot>{}{}</mroot>", self.parse_argument(), index),
+                None => format!("<msqrt>{}</msqrt>", self.parse_argument()),
+            },
+            "left" => {
+                let open = self.parse_delimiter();
+                let row = self.parse_row();
+                let close = if self.peek() == Some(&Token::Command("right".to_string())) {
+                    self.pos += 1;
+                    self.parse_delimiter()
+                } else {
+                    String::new()
+                };
+                format!("<mrow>{}{}{}</mrow>", open, row, close)
+            }
+            "text" | "textrm" | "mbox" | "textnormal" => {
+                format!("<mtext>{}</mtext>", escape(&self.parse_text_argument()))
+            }
+            "operatorname" | "mathrm" => {
+                format!(
+                    r#"<mi mathvariant="normal">{}</mi>"#,
+                    escape(&self.parse_text_argument())
+                )
+            }
+            "mathbb" | "mathbf" | "boldsymbol" | "mathcal" | "mathfrak" | "mathit" | "mathsf"
+            | "mathtt" | "textbf" | "textit" => {
+                let variant = match name {
+                    "mathbb" => "double-struck",
+                    "mathbf" | "textbf" => "bold",
+                    "boldsymbol" => "bold-italic",
+                    "mathcal" => "script",
+                    "mathfrak" => "fraktur",
+                    "mathsf" => "sans-serif",
+                    "mathtt" => "monospace",
+                    _ => "italic",
+                };
+                format!(
+                    r#"<mi mathvariant="{}">{}</mi>"#,
+                    variant,
+                    escape(&self.parse_text_argument())
+                )
+            }
+            "hat" | "widehat" | "bar" | "overline" | "vec" | "tilde" | "widetilde" | "dot"
+            | "ddot" | "overrightarrow" => {
+                let accent = match name {
+                    "hat" | "widehat" => "^",
+                    "bar" | "overline" => "‾",
+                    "vec" | "overrightarrow" => "→",
+                    "tilde" | "widetilde" => "~",
+                    "dot" => "˙",
+                    _ => "¨",
+                };
+                format!(
+                    r#"<mover accent="true">{}<mo>{}</mo></mover>"#,
+                    self.parse_argument(),
+                    accent
+                )
+            }
+            "underline" => format!(
+                r#"<munder accentunder="true">{}<mo>_</mo></munder>"#,
+                self.parse_argument()
+            ),
+            "begin" => self.parse_environment(),
+            "," | ":" | ";" | " " | "quad" | "qquad" | "!" => {
+                let width = match name {
+                    "," => "0.1667em",
+                    ":" => "0.2222em",
+                    ";" => "0.2778em",
+                    " " => "0.25em",
+                    "quad" => "1em",
+                    "qquad" => "2em",
+                    _ => "-0.1667em",
+                };
+                format!(r#"<mspace width="{}"/>"#, width)
+            }
+            "{" | "}" | "|" | "%" | "$" | "#" | "&" | "_" => {
+                format!("<mo>{}</mo>", escape(if name == "|" { "‖" } else { name }))
+            }
+            "\\" => return None,
+            "displaystyle" | "textstyle" | "limits" | "nolimits" | "big" | "Big" | "bigg"
+            | "Bigg" | "bigl" | "bigr" | "Bigl" | "Bigr" => return None,
+            _ => {
+                if FUNCTIONS.contains(&name) {
+                    let limits = LIMIT_FUNCTIONS.contains(&name);
+                    return Some((format!("<mi>{}</mi>", name), limits));
+                }
+                return match symbol(name) {
+                    Some((symbol, SymbolKind::Identifier)) => {
+                        Some((format!("<mi>{}</mi>", symbol), false))
+                    }
+                    Some((symbol, SymbolKind::Operator)) => {
+                        Some((format!("<mo>{}</mo>", symbol), false))
+                    }
+                    Some((symbol, SymbolKind::LargeOperator)) => Some((
+                        format!(r#"<mo largeop="true" movablelimits="true">{}</mo>"#, symbol),
+                        // Integrals keep their limits to the side
+                        !symbol.starts_with(['∫', '∬', '∭', '∮']),
+                    )),
+                    None => Some((
+                        format!("<merror><mtext>\\{}</mtext></merror>", escape(name)),
+                        false,
+                    )),
+                };
+            }
+        };
+        Some((atom, false))
+    }
+
+    // The delimiter after \left or \right; `.` means none
+    fn parse_delimiter(&mut self) -> String {
+        self.skip_spaces();
+        let delimiter = match self.tokens.get(self.pos).cloned() {
+            Some(Token::Char('.')) => String::new(),
+            Some(Token::Char(c)) => operator(c),
+            Some(Token::Command(name)) => match name.as_str() {
+                "{" => "{".to_string(),
+                "}" => "}".to_string(),
+                "|" => "‖".to_string(),
+                other => symbol(other)
+                    .map(|(s, _)| s.to_string())
+                    .unwrap_or_default(),
+            },
+            _ => return String::new(),
+        };
+        self.pos += 1;
+        if delimiter.is_empty() {
+            String::new()
+        } else {
+            format!(r#"<mo fence="true">{}</mo>"#, escape(&delimiter))
+        }
+    }
+
+    // \begin{name} ... \end{name}: matrices, cases and aligned equations
+    fn parse_environment(&mut self) -> String {
+        let name = self.parse_text_argument();
+        let name = name.trim().trim_end_matches('*');
+        if name == "array" {
+            // Column spec isn't needed for layout here
+            self.parse_text_argument();
+        }
+
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+        loop {
+            let cell = self.parse_row();
+            cells.push(format!("<mtd>{}</mtd>", cell));
+            match self.tokens.get(self.pos).cloned() {
+                Some(Token::Align) => self.pos += 1,
+                Some(Token::Command(command)) if command == "\\" => {
+                    self.pos += 1;
+                    rows.push(format!("<mtr>{}</mtr>", cells.concat()));
+                    cells.clear();
+                }
+                Some(Token::Command(command)) if command == "end" => {
+                    self.pos += 1;
+                    self.parse_text_argument();
+                    break;
+                }
+                // `}` or `\right` without a matching \end
+                Some(_) => self.pos += 1,
+                None => break,
+            }
+        }
+        // A trailing `\\` leaves an empty last row
+        if cells.len() > 1 || cells.first().is_some_and(|cell| cell != "<mtd></mtd>") {
+            rows.push(format!("<mtr>{}</mtr>", cells.concat()));
+        }
+
+        let table = match name {
+            "aligned" | "align" | "split" | "gathered" => format!(
+                r#"<mtable columnalign="right left" columnspacing="0">{}</mtable>"#,
+                rows.concat()
+            ),
+            "cases" => format!(
+                r#"<mtable columnalign="left left">{}</mtable>"#,
+                rows.concat()
+            ),
+            _ => format!("<mtable>{}</mtable>", rows.concat()),
+        };
+        let (open, close) = match name {
+            "pmatrix" => ("(", ")"),
+            "bmatrix" => ("[", "]"),
+            "Bmatrix" => ("{", "}"),
+            "vmatrix" => ("|", "|"),
+            "Vmatrix" => ("‖", "‖"),
+            "cases" => ("{", ""),
+            _ => ("", ""),
+        };
+        if open.is_empty() {
+            return table;
+        }
+        let close = if close.is_empty() {
+            String::new()
+        } else {
+            format!(r#"<mo fence="true">{}</mo>"#, close)
+        };
+        format!(
+            r#"<mrow><mo fence="true">{}</mo>{}{}</mrow>"#,
+            open, table, close
+        )
+    }
+}
+
+fn escape(text: &str) -> String {
+    html_escape::encode_text(text).to_string()
+}
+
+fn operator(c: char) -> String {
+    match c {
+        '-' => "−".to_string(),
+        '*' => "∗".to_string(),
+        '\'' => "′".to_string(),
+        c => escape(&c.to_string()),
+    }
+}
+
+// Upright function names; the second list takes limits below in display mode
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "lim", "liminf", "limsup", "max", "min", "sup", "inf", "arg", "deg",
+    "det", "dim", "gcd", "hom", "ker", "Pr",
+];
+const LIMIT_FUNCTIONS: &[&str] = &[
+    "lim", "liminf", "limsup", "max", "min", "sup", "inf", "det", "gcd", "Pr",
+];
+
+enum SymbolKind {
+    Identifier,
+    Operator,
+    LargeOperator,
+}
+
+fn symbol(name: &str) -> Option<(&'static str, SymbolKind)> {
+    use SymbolKind::{Identifier, LargeOperator, Operator};
+    let symbol = match name {
+        // Greek
+        "alpha" => ("α", Identifier),
+        "beta" => ("β", Identifier),
+        "gamma" => ("γ", Identifier),
+        "delta" => ("δ", Identifier),
+        "epsilon" => ("ϵ", Identifier),
+        "varepsilon" => ("ε", Identifier),
+        "zeta" => ("ζ", Identifier),
+        "eta" => ("η", Identifier),
+        "theta" => ("θ", Identifier),
+        "vartheta" => ("ϑ", Identifier),
+        "iota" => ("ι", Identifier),
+        "kappa" => ("κ", Identifier),
+        "lambda" => ("λ", Identifier),
+        "mu" => ("μ", Identifier),
+        "nu" => ("ν", Identifier),
+        "xi" => ("ξ", Identifier),
+        "pi" => ("π", Identifier),
+        "varpi" => ("ϖ", Identifier),
+        "rho" => ("ρ", Identifier),
+        "varrho" => ("ϱ", Identifier),
+        "sigma" => ("σ", Identifier),
+        "varsigma" => ("ς", Identifier),
+        "tau" => ("τ", Identifier),
+        "upsilon" => ("υ", Identifier),
+        "phi" => ("ϕ", Identifier),
+        "varphi" => ("φ", Identifier),
+        "chi" => ("χ", Identifier),
+        "psi" => ("ψ", Identifier),
+        "omega" => ("ω", Identifier),
+        "Gamma" => ("Γ", Identifier),
+        "Delta" => ("Δ", Identifier),
+        "Theta" => ("Θ", Identifier),
+        "Lambda" => ("Λ", Identifier),
+        "Xi" => ("Ξ", Identifier),
+        "Pi" => ("Π", Identifier),
+        "Sigma" => ("Σ", Identifier),
+        "Upsilon" => ("Υ", Identifier),
+        "Phi" => ("Φ", Identifier),
+        "Psi" => ("Ψ", Identifier),
+        "Omega" => ("Ω", Identifier),
+        // Letter-like
+        "infty" => ("∞", Identifier),
+        "partial" => ("∂", Identifier),
+        "nabla" => ("∇", Identifier),
+        "ell" => ("ℓ", Identifier),
+        "hbar" => ("ℏ", Identifier),
+        "emptyset" | "varnothing" => ("∅", Identifier),
+        "aleph" => ("ℵ", Identifier),
+        "Re" => ("ℜ", Identifier),
+        "Im" => ("ℑ", Identifier),
+        // Large operators
+        "sum" => ("∑", LargeOperator),
+        "prod" => ("∏", LargeOperator),
+        "coprod" => ("∐", LargeOperator),
+        "int" => ("∫", LargeOperator),
+        "iint" => ("∬", LargeOperator),
+        "iiint" => ("∭", LargeOperator),
+        "oint" => ("∮", LargeOperator),
+        "bigcup" => ("⋃", LargeOperator),
+        "bigcap" => ("⋂", LargeOperator),
+        "bigoplus" => ("⨁", LargeOperator),
+        "bigotimes" => ("⨂", LargeOperator),
+        "bigvee" => ("⋁", LargeOperator),
+        "bigwedge" => ("⋀", LargeOperator),
+        // Binary operators
+        "pm" => ("±", Operator),
+        "mp" => ("∓", Operator),
+        "times" => ("×", Operator),
+        "div" => ("÷", Operator),
+        "cdot" => ("⋅", Operator),
+        "ast" => ("∗", Operator),
+        "star" => ("⋆", Operator),
+        "circ" => ("∘", Operator),
+        "bullet" => ("∙", Operator),
+        "oplus" => ("⊕", Operator),
+        "otimes" => ("⊗", Operator),
+        "cup" => ("∪", Operator),
+        "cap" => ("∩", Operator),
+        "setminus" => ("∖", Operator),
+        "wedge" | "land" => ("∧", Operator),
+        "vee" | "lor" => ("∨", Operator),
+        "neg" | "lnot" => ("¬", Operator),
+        // Relations
+        "leq" | "le" => ("≤", Operator),
+        "geq" | "ge" => ("≥", Operator),
+        "neq" | "ne" => ("≠", Operator),
+        "approx" => ("≈", Operator),
+        "equiv" => ("≡", Operator),
+        "sim" => ("∼", Operator),
+        "simeq" => ("≃", Operator),
+        "cong" => ("≅", Operator),
+        "propto" => ("∝", Operator),
+        "ll" => ("≪", Operator),
+        "gg" => ("≫", Operator),
+        "in" => ("∈", Operator),
+        "notin" => ("∉", Operator),
+        "ni" => ("∋", Operator),
+        "subset" => ("⊂", Operator),
+        "subseteq" => ("⊆", Operator),
+        "supset" => ("⊃", Operator),
+        "supseteq" => ("⊇", Operator),
+        "mid" => ("∣", Operator),
+        "parallel" => ("∥", Operator),
+        "perp" => ("⊥", Operator),
+        "forall" => ("∀", Operator),
+        "exists" => ("∃", Operator),
+        "nexists" => ("∄", Operator),
+        // Arrows
+        "to" | "rightarrow" => ("→", Operator),
+        "leftarrow" | "gets" => ("←", Operator),
+        "leftrightarrow" => ("↔", Operator),
+        "Rightarrow" | "implies" => ("⇒", Operator),
+        "Leftarrow" => ("⇐", Operator),
+        "Leftrightarrow" | "iff" => ("⇔", Operator),
+        "mapsto" => ("↦", Operator),
+        "uparrow" => ("↑", Operator),
+        "downarrow" => ("↓", Operator),
+        // Delimiters and dots
+        "langle" => ("⟨", Operator),
+        "rangle" => ("⟩", Operator),
+        "lfloor" => ("⌊", Operator),
+        "rfloor" => ("⌋", Operator),
+        "lceil" => ("⌈", Operator),
+        "rceil" => ("⌉", Operator),
+        "vert" => ("|", Operator),
+        "Vert" => ("‖", Operator),
+        "ldots" | "dots" => ("…", Operator),
+        "cdots" => ("⋯", Operator),
+        "vdots" => ("⋮", Operator),
+        "ddots" => ("⋱", Operator),
+        "prime" => ("′", Operator),
+        "angle" => ("∠", Operator),
+        "triangle" => ("△", Operator),
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(content: &str) -> Vec<String> {
+        extract_math(content)
+            .1
+            .into_iter()
+            .map(|formula| formula.tex)
+            .collect()
+    }
+
+    #[test]
+    fn extract_math_finds_inline_and_display_math() {
+        let (output, formulas) = extract_math("Euler: $e^{i\\pi} = -1$\n\n$$\nx^2\n$$\n");
+        assert_eq!(formulas.len(), 2);
+        assert_eq!(formulas[0].tex, "e^{i\\pi} = -1");
+        assert!(!formulas[0].display);
+        assert_eq!(formulas[1].tex, "x^2");
+        assert!(formulas[1].display);
+        assert!(!output.contains('$'));
+    }
+
+    #[test]
+    fn extract_math_leaves_prices_and_escapes() {
+        assert_eq!(sources("It costs $5 and $10."), Vec::<String>::new());
+        assert_eq!(sources("Pay $ 5 or $6"), Vec::<String>::new());
+        assert_eq!(sources("A literal \\$x\\$ here"), Vec::<String>::new());
+        assert_eq!(sources("$a\n\nb$"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn extract_math_skips_fenced_and_inline_code() {
+        let content = "```bash\necho $HOME/$USER\n```\n\nRun `echo $PATH $x$` then $y$.";
+        let (output, formulas) = extract_math(content);
+        assert_eq!(sources(content), ["y"]);
+        assert_eq!(formulas.len(), 1);
+        assert!(output.contains("echo $HOME/$USER"));
+        assert!(output.contains("`echo $PATH $x$`"));
+    }
+
+    #[test]
+    fn extract_math_skips_code_in_blockquotes_and_lists() {
+        let content = "> ```bash\n> echo $HOME/$USER\n> ```\n\n- item\n\n  ```\n  $a$\n  ```\n";
+        let (output, formulas) = extract_math(content);
+        assert_eq!(formulas.len(), 0);
+        assert_eq!(output, content);
+    }
+
+    #[test]
+    fn extract_math_skips_indented_code() {
+        let content = "Text\n\n    let price = $a$;\n\nAfter $b$";
+        assert_eq!(sources(content), ["b"]);
+    }
+
+    #[test]
+    fn extract_math_only_closes_a_fence_on_a_long_enough_fence() {
+        let content = "````markdown\n```\n$x$\n```\n$y$\n````\n\n$z$";
+        assert_eq!(sources(content), ["z"]);
+    }
+
+    #[test]
+    fn restore_source_swaps_placeholders_back() {
+        let (output, formulas) = extract_math("Area $\\pi r^2$ here");
+        assert_eq!(restore_source(&output, &formulas), "Area \\pi r^2 here");
+        assert_eq!(restore_markdown(&output, &formulas), "Area $\\pi r^2$ here");
+        assert_eq!(render_placeholders("no math", &formulas), None);
+    }
+
+    #[test]
+    fn latex_to_mathml_renders_common_constructs() {
+        let mathml = latex_to_mathml("x^2", false);
+        assert!(mathml
+            .starts_with("<math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"inline\">"));
+        assert!(mathml.contains("<msup><mi>x</mi><mn>2</mn></msup>"));
+        assert!(mathml.contains("<annotation encoding=\"application/x-tex\">x^2</annotation>"));
+
+        assert!(latex_to_mathml("\\frac{a}{b}", true).contains("display=\"block\""));
+        assert!(latex_to_mathml("\\frac{a}{b}", false)
+            .contains("<mfrac><mrow><mi>a</mi></mrow><mrow><mi>b</mi></mrow></mfrac>"));
+        assert!(latex_to_mathml("\\sqrt{x}", false).contains("<msqrt>"));
+        assert!(
+            latex_to_mathml("\\alpha + \\beta", false).contains("<mi>α</mi><mo>+</mo><mi>β</mi>")
+        );
+        assert!(latex_to_mathml("a_{i}^{2}", false).contains("<msubsup>"));
+    }
+
+    #[test]
+    fn latex_to_mathml_escapes_and_flags_unknown_commands() {
+        let mathml = latex_to_mathml("x < y", false);
+        assert!(mathml.contains("<mo>&lt;</mo>"));
+        assert!(mathml.contains(">x &lt; y</annotation>"));
+        assert!(latex_to_mathml("\\unknowncmd", false)
+            .contains("<merror><mtext>\\unknowncmd</mtext></merror>"));
+    }
+
+    #[test]
+    fn latex_to_mathml_falls_back_to_source_when_nested_too_deeply() {
+        let tex = format!("{}x{}", "{".repeat(10_000), "}".repeat(10_000));
+        let mathml = latex_to_mathml(&tex, false);
+        assert!(mathml.contains(&format!("<merror><mtext>{}</mtext></merror>", tex)));
+        assert!(!mathml.contains("<mi>x</mi>"));
+
+        let frac = "\\frac{1}{".repeat(10_000);
+        assert!(latex_to_mathml(&frac, true).contains("<merror><mtext>\\frac{1}{"));
+
+        let depth = MAX_NESTING - 1;
+        let shallow = format!("{}x{}", "{".repeat(depth), "}".repeat(depth));
+        assert!(latex_to_mathml(&shallow, false).contains("<mi>x</mi>"));
+    }
+}
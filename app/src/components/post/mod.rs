@@ -1,8 +1,26 @@
+pub mod card;
 pub mod code;
+pub mod diagram;
 pub mod header;
+pub mod images;
+#[cfg(feature = "ssr")]
+pub mod index;
 pub mod interactions;
 pub mod markdown;
+pub mod math;
+pub mod related;
+#[cfg(feature = "ssr")]
+pub mod render;
+#[cfg(feature = "ssr")]
+pub mod sanitize;
+#[cfg(feature = "ssr")]
+pub mod search;
+pub mod series;
+#[cfg(feature = "ssr")]
+pub mod store;
+pub mod toc;
 pub mod types;
+pub mod video;
 
 // Re-export everything from types and interactions for convenience
 pub use interactions::*;
@@ -10,4 +28,4 @@ pub use types::*;
 
 // Re-export server functions conditionally
 #[cfg(feature = "ssr")]
-pub use types::{get_all_posts, get_post_by_slug, increment_view, update_vote};
+pub use types::{get_all_posts, get_post_by_slug, increment_view, set_metrics_store, update_vote};
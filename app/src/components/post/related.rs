@@ -0,0 +1,57 @@
+use crate::components::post::{card::PostCard, PostSummary, RelatedPosts};
+use crate::routes::post::get_related_posts;
+use leptos::*;
+use leptos_router::*;
+
+// Links to the previous and next post by date, and posts on similar topics
+#[component]
+pub fn ReadNext(slug: String) -> impl IntoView {
+    let related = create_resource(move || slug.clone(), get_related_posts);
+
+    view! {
+        <Suspense fallback=|| ()>
+            {move || related.get().and_then(Result::ok).map(read_next_view)}
+        </Suspense>
+    }
+}
+
+fn read_next_view(related: RelatedPosts) -> View {
+    let RelatedPosts {
+        previous,
+        next,
+        related,
+    } = related;
+
+    view! {
+        {(previous.is_some() || next.is_some()).then(|| view! {
+            <nav aria-label="More posts" class="mt-8 grid grid-cols-1 sm:grid-cols-2 gap-3 sm:gap-4">
+                {previous.map(|post| neighbour_link(post, "← Previous post", ""))}
+                {next.map(|post| neighbour_link(post, "Next post →", "sm:col-start-2 sm:text-right"))}
+            </nav>
+        })}
+
+        {(!related.is_empty()).then(|| view! {
+            <section class="mt-12">
+                <h2 class="text-lg sm:text-xl font-bold mb-4 text-foreground">"Related posts"</h2>
+                <div class="space-y-2 sm:space-y-3 md:space-y-4">
+                    {related.into_iter()
+                        .map(|post| view! { <PostCard post=post/> })
+                        .collect_view()}
+                </div>
+            </section>
+        })}
+    }
+    .into_view()
+}
+
+fn neighbour_link(post: PostSummary, label: &'static str, class: &'static str) -> impl IntoView {
+    view! {
+        <A
+            href=format!("/post/{}", post.slug)
+            class=format!("block border-2 border-border px-4 py-3 hover:bg-accent hover:border-ring transition-colors {}", class)
+        >
+            <span class="block text-xs sm:text-sm text-muted-foreground">{label}</span>
+            <span class="block font-semibold text-foreground truncate">{post.title}</span>
+        </A>
+    }
+}
@@ -0,0 +1,98 @@
+// Server-side rendering of a post's Markdown into elements, shipped to the client with the
+// post so hydration doesn't parse Markdown again in WASM.
+//
+// Renders are cached per slug under a hash of the post, so unchanged posts are parsed once
+// per process however often they are requested. Images and videos are only looked up when
+// a post is rendered; the cache is cleared when files in the assets directory change or
+// image variants finish, so new variants, posters and subtitles still show up.
+
+use super::diagram::render_diagram;
+use super::images::image_assets;
+use super::markdown::{parse_markdown, MarkdownElement, ParsedMarkdown};
+use super::sanitize::sanitize_elements;
+use super::types::{content_hash, BlogPost};
+use super::video::{video_assets, VideoAssets, VideoSource};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+// Bump whenever parsing or rendering changes what a post renders to
+const RENDERER_VERSION: u32 = 1;
+
+// Post hash and render by slug, so there is at most one entry per post
+#[derive(Default)]
+struct RenderCache {
+    renders: HashMap<String, (String, ParsedMarkdown)>,
+    // Bumped on every clear, so a render that started before it isn't stored after it
+    generation: u64,
+}
+
+static CACHE: LazyLock<Mutex<RenderCache>> = LazyLock::new(Mutex::default);
+
+/// A post's rendered Markdown: sanitized unless trusted, with diagram SVGs, image
+/// variants and video posters/subtitles filled in.
+pub fn render_post(post: &BlogPost) -> ParsedMarkdown {
+    let key = content_hash(
+        format!("{}\0{}\0{}", RENDERER_VERSION, post.trusted, post.content).as_bytes(),
+    );
+    let generation = {
+        let cache = CACHE.lock().unwrap();
+        if let Some((cached_key, parsed)) = cache.renders.get(&post.slug) {
+            if *cached_key == key {
+                return parsed.clone();
+            }
+        }
+        cache.generation
+    };
+
+    let images = image_assets(&post.content);
+    let videos = video_assets(&post.content);
+    let mut parsed = parse_markdown(&post.content, Some(&format!("{}.md", post.slug)), &images);
+    if !post.trusted {
+        sanitize_elements(&mut parsed.elements, &parsed.ids);
+    }
+    let diagrams_drawn = attach_assets(&mut parsed.elements, &videos);
+
+    // A diagram that didn't draw may have hit a mermaid-cli hiccup, so the render is redone
+    // next time; diagrams that are simply broken come from the diagram cache then
+    let mut cache = CACHE.lock().unwrap();
+    if diagrams_drawn && cache.generation == generation {
+        cache
+            .renders
+            .insert(post.slug.clone(), (key, parsed.clone()));
+    }
+    parsed
+}
+
+/// Drop the cached render of a post that was deleted or no longer parses.
+pub fn forget_render(slug: &str) {
+    CACHE.lock().unwrap().renders.remove(slug);
+}
+
+/// Drop every cached render, after images or video files they may include have changed.
+pub fn clear_renders() {
+    let mut cache = CACHE.lock().unwrap();
+    cache.renders.clear();
+    cache.generation += 1;
+}
+
+// Diagram SVGs and video posters/subtitles, which live outside the Markdown. Returns whether
+// every diagram was drawn
+fn attach_assets(elements: &mut [MarkdownElement], videos: &HashMap<String, VideoAssets>) -> bool {
+    let mut drawn = true;
+    for element in elements {
+        match element {
+            MarkdownElement::Diagram { kind, source, svg } => {
+                *svg = render_diagram(*kind, source);
+                drawn &= svg.is_some();
+            }
+            MarkdownElement::Video(video) => {
+                if let VideoSource::File { url, .. } = &video.source {
+                    video.assets = videos.get(url).cloned().unwrap_or_default();
+                }
+            }
+            MarkdownElement::Callout { elements, .. } => drawn &= attach_assets(elements, videos),
+            _ => {}
+        }
+    }
+    drawn
+}
@@ -0,0 +1,177 @@
+// Allowlist sanitizer for rendered post HTML, so posts without `trusted: true` can't
+// inject scripts, iframes, forms or event handlers through raw HTML in their Markdown.
+//
+// The allowlist is ammonia's defaults plus what the Markdown renderer itself produces:
+// heading ids and anchors, `<picture>` images, task list checkboxes and MathML. Classes are
+// limited to the renderer's own, and ids it didn't generate are prefixed, so a post can't
+// restyle the page or take over ids the page relies on.
+
+use super::markdown::MarkdownElement;
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+const MATHML_TAGS: &[&str] = &[
+    "math",
+    "semantics",
+    "annotation",
+    "mrow",
+    "mi",
+    "mn",
+    "mo",
+    "mtext",
+    "mspace",
+    "merror",
+    "mfrac",
+    "msqrt",
+    "mroot",
+    "msub",
+    "msup",
+    "msubsup",
+    "munder",
+    "mover",
+    "munderover",
+    "mtable",
+    "mtr",
+    "mtd",
+];
+
+const MATHML_ATTRIBUTES: &[&str] = &[
+    "display",
+    "encoding",
+    "mathvariant",
+    "fence",
+    "largeop",
+    "movablelimits",
+    "accent",
+    "accentunder",
+    "linethickness",
+    "columnalign",
+    "columnspacing",
+    "width",
+];
+
+// Classes the renderer puts on markup that goes through the sanitizer, by tag: heading anchors,
+// images and figures (see `images.rs`) and footnotes
+const RENDERER_CLASSES: &[(&str, &[&str])] = &[
+    ("a", &["heading-anchor"]),
+    (
+        "img",
+        &["w-full", "h-auto", "border-2", "border-border", "my-8"],
+    ),
+    ("figure", &["my-8"]),
+    (
+        "figcaption",
+        &["mt-3", "text-sm", "text-muted-foreground", "text-center"],
+    ),
+    ("span", &["font-semibold", "text-foreground"]),
+    ("sup", &["footnote-reference", "footnote-definition-label"]),
+    ("div", &["footnote-definition"]),
+];
+
+// Put in front of ids from raw HTML, as GitHub does
+const AUTHOR_ID_PREFIX: &str = "user-content-";
+
+// The allowlist for one post; `renderer_ids` are the ids its renderer generated, which are kept
+fn sanitizer(renderer_ids: HashSet<String>) -> ammonia::Builder<'static> {
+    let mut builder = ammonia::Builder::default();
+    builder
+        // Links stay as written; heading anchors and footnotes are same-page links
+        .link_rel(None)
+        .add_generic_attributes(["id", "role", "aria-label", "aria-hidden"])
+        .add_tags(["picture", "source", "input"])
+        .add_tags(MATHML_TAGS)
+        .add_tag_attributes("img", ["loading", "decoding", "title"])
+        .add_tag_attributes("source", ["type", "srcset", "sizes"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        // Only the read-only checkboxes of task lists
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .set_tag_attribute_value("input", "disabled", "");
+    for tag in MATHML_TAGS {
+        builder.add_tag_attributes(tag, MATHML_ATTRIBUTES);
+    }
+    for (tag, classes) in RENDERER_CLASSES {
+        builder.add_allowed_classes(*tag, *classes);
+    }
+    builder.attribute_filter(move |_, attribute, value| {
+        if attribute == "id" && !renderer_ids.contains(value) {
+            Some(Cow::Owned(format!("{}{}", AUTHOR_ID_PREFIX, value)))
+        } else {
+            Some(Cow::Borrowed(value))
+        }
+    });
+    builder
+}
+
+// Sanitize the HTML chunks of parsed Markdown. The other elements never carry HTML from the
+// post, so running them through the allowlist would only strip markup we generate:
+// - code blocks hold plain text; the highlighter HTML-escapes every token it wraps in a span
+// - dot diagrams come from layout-rs, which escapes labels, and Mermaid SVGs are rendered in
+//   strict security mode, which escapes label HTML and can't be loosened from the post
+// - videos are built from a parsed file URL or a validated YouTube/Vimeo id, with every
+//   attribute value escaped
+pub fn sanitize_elements<S: std::hash::BuildHasher>(
+    elements: &mut [MarkdownElement],
+    renderer_ids: &HashSet<String, S>,
+) {
+    clean_elements(&sanitizer(renderer_ids.iter().cloned().collect()), elements);
+}
+
+fn clean_elements(sanitizer: &ammonia::Builder<'_>, elements: &mut [MarkdownElement]) {
+    for element in elements {
+        match element {
+            MarkdownElement::Html(html) => *html = sanitizer.clean(html).to_string(),
+            MarkdownElement::Callout { elements, .. } => clean_elements(sanitizer, elements),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::post::images::{figure_html, image_html};
+
+    fn sanitize_html(html: &str) -> String {
+        let renderer_ids = HashSet::from(["intro".to_string(), "fig-chart".to_string()]);
+        sanitizer(renderer_ids).clean(html).to_string()
+    }
+
+    #[test]
+    fn scripts_and_event_handlers_are_removed() {
+        let html = sanitize_html(
+            r#"<p onclick="x()">Hi<script>alert(1)</script></p><iframe src="x"></iframe>"#,
+        );
+        assert_eq!(html, "<p>Hi</p>");
+    }
+
+    #[test]
+    fn renderer_markup_is_kept() {
+        let heading = r##"<h2 id="intro">Intro<a class="heading-anchor" href="#intro">#</a></h2>"##;
+        assert_eq!(sanitize_html(heading), heading);
+        let figure = figure_html(
+            "fig-chart",
+            1,
+            "Sales",
+            &image_html("/a.png", "A", "", None),
+        );
+        assert_eq!(sanitize_html(&figure), figure);
+        let math = r#"<math display="inline"><mrow><mi>x</mi></mrow></math>"#;
+        assert_eq!(sanitize_html(math), math);
+        assert_eq!(
+            sanitize_html(r#"<input type="text" onfocus="x()" checked="">"#),
+            r#"<input type="checkbox" checked="" disabled="">"#
+        );
+    }
+
+    #[test]
+    fn author_classes_and_ids_are_contained() {
+        assert_eq!(
+            sanitize_html(r#"<div class="fixed inset-0 footnote-definition" id="app">x</div>"#),
+            r#"<div class="footnote-definition" id="user-content-app">x</div>"#
+        );
+        assert_eq!(
+            sanitize_html(r#"<p class="text-destructive">x</p>"#),
+            "<p>x</p>"
+        );
+    }
+}
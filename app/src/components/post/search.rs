@@ -0,0 +1,487 @@
+use super::types::{BlogPost, PostSummary, SearchHit};
+use std::collections::HashMap;
+
+// BM25 tuning constants
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+// Per-field weights: a hit in the title counts far more than one in the body
+const TITLE_WEIGHT: f32 = 4.0;
+const TAG_WEIGHT: f32 = 3.0;
+const EXCERPT_WEIGHT: f32 = 2.0;
+const BODY_WEIGHT: f32 = 1.0;
+
+const SNIPPET_RADIUS: usize = 80;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it",
+    "of", "on", "or", "so", "that", "the", "their", "then", "there", "these", "this", "to", "was",
+    "were", "will", "with",
+];
+
+#[derive(Debug, Clone)]
+struct Posting {
+    doc: usize,
+    weight: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Document {
+    slug: String,
+    length: f32,
+}
+
+/// Inverted index over post titles, tags, excerpts and bodies, ranked with BM25.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    documents: Vec<Document>,
+    postings: HashMap<String, Vec<Posting>>,
+    average_length: f32,
+    // Unit-length TF-IDF vector of each post body, for finding similar posts
+    vectors: Vec<HashMap<String, f32>>,
+}
+
+impl SearchIndex {
+    /// Index `posts`. Only those that are `listed` count towards term rarity for similar
+    /// posts, so drafts can't make a word look distinctive; they are still indexed, and
+    /// left out of results by the caller.
+    pub fn build<'a>(
+        posts: impl IntoIterator<Item = &'a BlogPost>,
+        listed: impl Fn(&BlogPost) -> bool,
+    ) -> Self {
+        let mut index = Self::default();
+
+        let mut body_terms: Vec<(HashMap<String, f32>, bool)> = Vec::new();
+        for post in posts {
+            let doc = index.documents.len();
+            let mut weights: HashMap<String, f32> = HashMap::new();
+            let mut length = 0.0;
+
+            let fields = [
+                (post.title.as_str(), TITLE_WEIGHT),
+                (post.excerpt.as_str(), EXCERPT_WEIGHT),
+                (post.content.as_str(), BODY_WEIGHT),
+            ];
+            let tags = post.tags.iter().map(|tag| (tag.as_str(), TAG_WEIGHT));
+
+            for (text, weight) in fields.into_iter().chain(tags) {
+                for term in tokenize(text) {
+                    *weights.entry(term).or_default() += weight;
+                    length += weight;
+                }
+            }
+
+            for (term, weight) in weights {
+                index
+                    .postings
+                    .entry(term)
+                    .or_default()
+                    .push(Posting { doc, weight });
+            }
+            index.documents.push(Document {
+                slug: post.slug.clone(),
+                length,
+            });
+
+            let mut counts: HashMap<String, f32> = HashMap::new();
+            for term in tokenize(&post.content) {
+                *counts.entry(term).or_default() += 1.0;
+            }
+            body_terms.push((counts, listed(post)));
+        }
+        index.vectors = tf_idf_vectors(body_terms);
+
+        let total: f32 = index.documents.iter().map(|d| d.length).sum();
+        index.average_length = total / index.documents.len().max(1) as f32;
+        index
+    }
+
+    /// Slugs matching `query` with their scores, best match first.
+    ///
+    /// The last query term also matches as a prefix so results update while typing.
+    pub fn search(&self, query: &str) -> Vec<(String, f32)> {
+        let terms = tokenize(query);
+        let Some(last) = terms.last() else {
+            return Vec::new();
+        };
+
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+        for (position, term) in terms.iter().enumerate() {
+            let is_last = position + 1 == terms.len();
+            let matching: Vec<&Vec<Posting>> = if is_last && last.len() >= 2 {
+                self.postings
+                    .iter()
+                    .filter(|(indexed, _)| indexed.starts_with(term.as_str()))
+                    .map(|(_, postings)| postings)
+                    .collect()
+            } else {
+                self.postings.get(term).into_iter().collect()
+            };
+
+            for postings in matching {
+                let idf = self.idf(postings.len());
+                for posting in postings {
+                    let doc = &self.documents[posting.doc];
+                    let norm = K1 * (1.0 - B + B * doc.length / self.average_length);
+                    let score = idf * posting.weight * (K1 + 1.0) / (posting.weight + norm);
+                    *scores.entry(posting.doc).or_default() += score;
+                }
+            }
+        }
+
+        let mut results: Vec<(String, f32)> = scores
+            .into_iter()
+            .map(|(doc, score)| (self.documents[doc].slug.clone(), score))
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1));
+        results
+    }
+
+    /// Other posts by cosine similarity of their bodies to the post with `slug`, most
+    /// similar first; posts sharing no distinctive terms are left out.
+    pub fn similar(&self, slug: &str) -> Vec<(String, f32)> {
+        let Some(doc) = self.documents.iter().position(|d| d.slug == slug) else {
+            return Vec::new();
+        };
+        let target = &self.vectors[doc];
+
+        let mut results: Vec<(String, f32)> = self
+            .vectors
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != doc)
+            .map(|(other, vector)| (self.documents[other].slug.clone(), cosine(target, vector)))
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1));
+        results
+    }
+
+    fn idf(&self, document_frequency: usize) -> f32 {
+        let n = self.documents.len() as f32;
+        let df = document_frequency as f32;
+        ((n - df + 0.5) / (df + 0.5)).ln_1p()
+    }
+}
+
+// Log-scaled term frequency times inverse document frequency, normalised to unit length.
+// Document frequencies come from the listed posts only; terms found in every listed post,
+// or in none, get no weight.
+fn tf_idf_vectors(counts: Vec<(HashMap<String, f32>, bool)>) -> Vec<HashMap<String, f32>> {
+    let mut document_frequency: HashMap<&str, f32> = HashMap::new();
+    for (terms, _) in counts.iter().filter(|(_, listed)| *listed) {
+        for term in terms.keys() {
+            *document_frequency.entry(term).or_default() += 1.0;
+        }
+    }
+    let n = counts.iter().filter(|(_, listed)| *listed).count() as f32;
+
+    counts
+        .iter()
+        .map(|(terms, _)| {
+            let mut vector: HashMap<String, f32> = terms
+                .iter()
+                .filter_map(|(term, count)| {
+                    let idf = (n / document_frequency.get(term.as_str())?).ln();
+                    Some((term.clone(), (1.0 + count.ln()) * idf))
+                })
+                .filter(|(_, weight)| *weight > 0.0)
+                .collect();
+            let norm = vector.values().map(|w| w * w).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                for weight in vector.values_mut() {
+                    *weight /= norm;
+                }
+            }
+            vector
+        })
+        .collect()
+}
+
+// Both vectors are unit length, so this is the dot product
+fn cosine(a: &HashMap<String, f32>, b: &HashMap<String, f32>) -> f32 {
+    let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    small
+        .iter()
+        .filter_map(|(term, weight)| large.get(term).map(|other| weight * other))
+        .sum()
+}
+
+/// Turn ranked slugs back into hits with a highlighted body snippet.
+pub fn to_hits(query: &str, ranked: Vec<(String, f32)>, posts: &[BlogPost]) -> Vec<SearchHit> {
+    let terms = tokenize(query);
+    ranked
+        .into_iter()
+        .filter_map(|(slug, score)| {
+            let post = posts.iter().find(|p| p.slug == slug)?;
+            let snippet = snippet(&post.content, &terms)
+                .unwrap_or_else(|| html_escape::encode_text(&post.excerpt).to_string());
+            Some(SearchHit {
+                post: PostSummary::from(post),
+                score,
+                snippet,
+            })
+        })
+        .collect()
+}
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| stem(&word))
+        .collect()
+}
+
+// Light suffix-stripping stemmer covering the common English inflections
+pub fn stem(word: &str) -> String {
+    const SUFFIXES: &[(&str, &str)] = &[
+        ("ational", "ate"),
+        ("ization", "ize"),
+        ("fulness", "ful"),
+        ("iveness", "ive"),
+        ("ements", ""),
+        ("ement", ""),
+        ("ments", ""),
+        ("ment", ""),
+        ("ness", ""),
+        ("ings", ""),
+        ("ing", ""),
+        ("ies", "y"),
+        ("ied", "y"),
+        ("edly", ""),
+        ("ly", ""),
+        ("ed", ""),
+        ("es", ""),
+        ("s", ""),
+    ];
+
+    if word.len() <= 3 || !word.is_ascii() {
+        return word.to_string();
+    }
+
+    let mut stemmed = strip_suffix(word, SUFFIXES);
+    // A trailing "e" is dropped so "page" meets "pages" and "release" meets "released"
+    if stemmed.len() > 3 && stemmed.ends_with('e') {
+        stemmed.pop();
+    }
+    stemmed
+}
+
+fn strip_suffix(word: &str, suffixes: &[(&str, &str)]) -> String {
+    for (suffix, replacement) in suffixes {
+        if let Some(root) = word.strip_suffix(suffix) {
+            // "es" is only a plural ending after a sibilant ("boxes", "branches"); "pages"
+            // loses just the "s"
+            if *suffix == "es"
+                && !["s", "x", "z", "ch", "sh"]
+                    .iter()
+                    .any(|sibilant| root.ends_with(sibilant))
+            {
+                continue;
+            }
+            // Keep at least three characters so "sing" or "bed" survive intact
+            if root.len() >= 3 && !(*suffix == "s" && root.ends_with('s')) {
+                let mut stemmed = format!("{}{}", root, replacement);
+                // "running" -> "runn" -> "run"
+                let bytes = stemmed.as_bytes();
+                if replacement.is_empty()
+                    && bytes.len() >= 4
+                    && bytes[bytes.len() - 1] == bytes[bytes.len() - 2]
+                    && !matches!(bytes[bytes.len() - 1], b'l' | b's' | b'z')
+                {
+                    stemmed.pop();
+                }
+                return stemmed;
+            }
+        }
+    }
+
+    word.to_string()
+}
+
+// Find the first matching word in the body and return an escaped window around it,
+// with every matching word wrapped in <mark>
+fn snippet(content: &str, terms: &[String]) -> Option<String> {
+    if terms.is_empty() {
+        return None;
+    }
+
+    let text = plain_text(content);
+    let matches_term = |word: &str| {
+        let stemmed = stem(&word.to_lowercase());
+        terms.iter().enumerate().any(|(i, term)| {
+            stemmed == *term || (i + 1 == terms.len() && stemmed.starts_with(term))
+        })
+    };
+
+    let words = word_spans(&text);
+    let first = words
+        .iter()
+        .find(|(start, end)| matches_term(&text[*start..*end]))?;
+
+    let start = floor_char_boundary(&text, first.0.saturating_sub(SNIPPET_RADIUS));
+    let end = floor_char_boundary(&text, (first.1 + SNIPPET_RADIUS).min(text.len()));
+
+    let mut html = String::new();
+    if start > 0 {
+        html.push('…');
+    }
+    let mut cursor = start;
+    for (word_start, word_end) in words
+        .iter()
+        .filter(|(s, e)| *s >= start && *e <= end)
+        .filter(|(s, e)| matches_term(&text[*s..*e]))
+    {
+        html.push_str(&html_escape::encode_text(&text[cursor..*word_start]));
+        html.push_str("<mark>");
+        html.push_str(&html_escape::encode_text(&text[*word_start..*word_end]));
+        html.push_str("</mark>");
+        cursor = *word_end;
+    }
+    html.push_str(&html_escape::encode_text(&text[cursor..end]));
+    if end < text.len() {
+        html.push('…');
+    }
+
+    Some(html)
+}
+
+// Strip code fences and Markdown punctuation, collapsing whitespace
+fn plain_text(content: &str) -> String {
+    let mut in_fence = false;
+    let mut text = String::new();
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let cleaned: String = line
+            .chars()
+            .map(|c| match c {
+                '#' | '*' | '_' | '`' | '>' | '[' | ']' | '|' => ' ',
+                c => c,
+            })
+            .collect();
+        text.push_str(cleaned.trim());
+        text.push(' ');
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn word_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, text.len()));
+    }
+    spans
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn singular_and_plural_stem_to_the_same_term() {
+        for (singular, plural) in [
+            ("page", "pages"),
+            ("file", "files"),
+            ("image", "images"),
+            ("cache", "caches"),
+            ("box", "boxes"),
+            ("branch", "branches"),
+            ("class", "classes"),
+            ("query", "queries"),
+            ("post", "posts"),
+        ] {
+            assert_eq!(stem(singular), stem(plural), "{} / {}", singular, plural);
+        }
+    }
+
+    #[test]
+    fn verb_forms_stem_to_the_same_term() {
+        for (base, inflected) in [
+            ("release", "released"),
+            ("cache", "cached"),
+            ("run", "running"),
+            ("stop", "stopped"),
+            ("index", "indexed"),
+        ] {
+            assert_eq!(stem(base), stem(inflected), "{} / {}", base, inflected);
+        }
+    }
+
+    #[test]
+    fn short_and_non_ascii_words_are_left_alone() {
+        assert_eq!(stem("bed"), "bed");
+        assert_eq!(stem("sing"), "sing");
+        assert_eq!(stem("glass"), "glass");
+        assert_eq!(stem("cafés"), "cafés");
+    }
+
+    #[test]
+    fn tokenize_lowercases_splits_and_drops_stop_words() {
+        assert_eq!(
+            tokenize("The Pages of a Blog-Post"),
+            vec![stem("pages"), "blog".to_string(), "post".to_string()]
+        );
+        assert_eq!(tokenize("  --  "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn singular_query_finds_plural_in_body() {
+        let post = BlogPost {
+            slug: "p".to_string(),
+            content: "Static pages and cached images".to_string(),
+            ..BlogPost::default()
+        };
+        let index = SearchIndex::build([&post], |_| true);
+        for query in ["page", "image", "cache static"] {
+            assert_eq!(index.search(query).len(), 1, "{}", query);
+        }
+    }
+
+    #[test]
+    fn drafts_do_not_count_towards_term_rarity() {
+        let post = |slug: &str, content: &str| BlogPost {
+            slug: slug.to_string(),
+            content: content.to_string(),
+            ..BlogPost::default()
+        };
+        let posts = [
+            post("a", "rust ownership"),
+            post("b", "rust lifetimes"),
+            post("draft", "gardening tips"),
+        ];
+
+        // "rust" is in every listed post, so it says nothing about similarity
+        let index = SearchIndex::build(&posts, |post| post.slug != "draft");
+        assert_eq!(index.similar("a"), Vec::new());
+
+        let index = SearchIndex::build(&posts, |_| true);
+        let similar: Vec<String> = index.similar("a").into_iter().map(|(s, _)| s).collect();
+        assert_eq!(similar, ["b"]);
+    }
+}
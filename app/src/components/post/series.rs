@@ -0,0 +1,83 @@
+use crate::components::post::{markdown::slugify, series_href, Series};
+use crate::routes::series::get_series;
+use leptos::*;
+use leptos_router::*;
+
+// Every part of the series the post belongs to, with links to the parts either side of it
+#[component]
+pub fn SeriesNav(
+    // Series name as written in the post's frontmatter
+    series: String,
+    // Slug of the post being read
+    current: String,
+) -> impl IntoView {
+    let slug = slugify(&series);
+    let parts = create_resource(move || slug.clone(), get_series);
+    let current = store_value(current);
+
+    view! {
+        <Suspense fallback=|| ()>
+            {move || {
+                parts
+                    .get()
+                    .and_then(Result::ok)
+                    .flatten()
+                    .map(|series| current.with_value(|current| series_view(series, current)))
+            }}
+        </Suspense>
+    }
+}
+
+fn series_view(series: Series, current: &str) -> View {
+    let Some(position) = series.position(current) else {
+        return ().into_view();
+    };
+    let Series { name, parts, .. } = series;
+    let total = parts.len();
+    let previous = position.checked_sub(1).and_then(|i| parts.get(i)).cloned();
+    let next = parts.get(position + 1).cloned();
+
+    view! {
+        <nav aria-label="Series" class="mb-8 sm:mb-10 border-2 border-border px-4 py-3 text-sm sm:text-base">
+            <p class="text-muted-foreground mb-2">
+                {format!("Part {} of {} in ", position + 1, total)}
+                <A href=series_href(&name) class="font-semibold text-foreground hover:text-primary">
+                    {name.clone()}
+                </A>
+            </p>
+            <ol class="list-decimal pl-6 space-y-1 mb-3">
+                {parts.into_iter().map(|part| {
+                    if part.slug == current {
+                        view! {
+                            <li class="text-foreground font-semibold" aria-current="page">{part.title}</li>
+                        }
+                    } else {
+                        view! {
+                            <li>
+                                <A href=format!("/post/{}", part.slug) class="text-muted-foreground hover:text-foreground">
+                                    {part.title}
+                                </A>
+                            </li>
+                        }
+                    }
+                }).collect_view()}
+            </ol>
+            <div class="flex justify-between gap-4 text-sm">
+                {match previous {
+                    Some(part) => view! {
+                        <A href=format!("/post/{}", part.slug) class="text-primary hover:text-primary/80 truncate">
+                            "← "{part.title}
+                        </A>
+                    }.into_view(),
+                    None => view! { <span></span> }.into_view(),
+                }}
+                {next.map(|part| view! {
+                    <A href=format!("/post/{}", part.slug) class="text-primary hover:text-primary/80 truncate text-right ml-auto">
+                        {part.title}" →"
+                    </A>
+                })}
+            </div>
+        </nav>
+    }
+    .into_view()
+}
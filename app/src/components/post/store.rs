@@ -0,0 +1,263 @@
+use super::types::PostMetrics;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Backend for post engagement metrics (views, likes and dislikes).
+pub trait MetricsStore: Send + Sync {
+    fn get(&self, slug: &str) -> PostMetrics;
+    fn increment_view(&self, slug: &str);
+    fn record_vote(&self, slug: &str, is_like: bool);
+}
+
+// In-process store, lost on every restart
+#[derive(Default)]
+pub struct MemoryMetricsStore {
+    metrics: Mutex<HashMap<String, PostMetrics>>,
+}
+
+impl MetricsStore for MemoryMetricsStore {
+    fn get(&self, slug: &str) -> PostMetrics {
+        let metrics = self.metrics.lock().unwrap();
+        metrics.get(slug).cloned().unwrap_or_default()
+    }
+
+    fn increment_view(&self, slug: &str) {
+        let mut metrics = self.metrics.lock().unwrap();
+        apply_event(
+            &mut metrics,
+            &MetricsEvent::View {
+                slug: slug.to_string(),
+            },
+        );
+    }
+
+    fn record_vote(&self, slug: &str, is_like: bool) {
+        let mut metrics = self.metrics.lock().unwrap();
+        apply_event(&mut metrics, &MetricsEvent::vote(slug, is_like));
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum MetricsEvent {
+    View {
+        slug: String,
+    },
+    Like {
+        slug: String,
+    },
+    Dislike {
+        slug: String,
+    },
+    // Running totals for a slug, written when the log is compacted
+    Totals {
+        slug: String,
+        views: u64,
+        likes: u64,
+        dislikes: u64,
+    },
+}
+
+impl MetricsEvent {
+    fn vote(slug: &str, is_like: bool) -> Self {
+        let slug = slug.to_string();
+        if is_like {
+            Self::Like { slug }
+        } else {
+            Self::Dislike { slug }
+        }
+    }
+}
+
+fn apply_event(metrics: &mut HashMap<String, PostMetrics>, event: &MetricsEvent) {
+    match event {
+        MetricsEvent::View { slug } => metrics.entry(slug.clone()).or_default().views += 1,
+        MetricsEvent::Like { slug } => metrics.entry(slug.clone()).or_default().likes += 1,
+        MetricsEvent::Dislike { slug } => metrics.entry(slug.clone()).or_default().dislikes += 1,
+        MetricsEvent::Totals {
+            slug,
+            views,
+            likes,
+            dislikes,
+        } => {
+            metrics.insert(
+                slug.clone(),
+                PostMetrics {
+                    views: *views,
+                    likes: *likes,
+                    dislikes: *dislikes,
+                },
+            );
+        }
+    }
+}
+
+/// Append-only log of metric events, one JSON object per line.
+///
+/// The log is replayed into memory when the store is opened, so reads never touch disk, and
+/// compacted to one line per slug so it does not grow without bound across restarts.
+pub struct FileMetricsStore {
+    path: PathBuf,
+    metrics: Mutex<HashMap<String, PostMetrics>>,
+    log: Mutex<File>,
+}
+
+impl FileMetricsStore {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut metrics = HashMap::new();
+        let mut entries = 0;
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path)?);
+            for (index, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                entries += 1;
+                match serde_json::from_str::<MetricsEvent>(&line) {
+                    Ok(event) => apply_event(&mut metrics, &event),
+                    // A torn write at the end of the log should not prevent startup
+                    Err(e) => tracing::warn!(
+                        "Skipping malformed metrics entry {}:{}: {}",
+                        path.display(),
+                        index + 1,
+                        e
+                    ),
+                }
+            }
+        }
+
+        if entries > metrics.len() {
+            compact(&path, &metrics)?;
+        }
+
+        let log = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(Self {
+            path,
+            metrics: Mutex::new(metrics),
+            log: Mutex::new(log),
+        })
+    }
+
+    fn record(&self, event: MetricsEvent) {
+        apply_event(&mut self.metrics.lock().unwrap(), &event);
+
+        // Append after releasing the metrics lock so readers never wait on disk I/O
+        let result = serde_json::to_string(&event)
+            .map_err(io::Error::from)
+            .and_then(|line| {
+                let mut log = self.log.lock().unwrap();
+                writeln!(log, "{}", line)?;
+                log.flush()
+            });
+        if let Err(e) = result {
+            tracing::error!(
+                "Failed to append to metrics log {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+// Replace the log with a single `Totals` line per slug. The new log is written beside the old
+// one and renamed over it, so a crash part way through leaves the original intact.
+fn compact(path: &Path, metrics: &HashMap<String, PostMetrics>) -> io::Result<()> {
+    let mut slugs: Vec<&String> = metrics.keys().collect();
+    slugs.sort();
+
+    let tmp = path.with_extension("compact");
+    let mut file = File::create(&tmp)?;
+    for slug in slugs {
+        let totals = &metrics[slug];
+        let event = MetricsEvent::Totals {
+            slug: slug.clone(),
+            views: totals.views,
+            likes: totals.likes,
+            dislikes: totals.dislikes,
+        };
+        writeln!(file, "{}", serde_json::to_string(&event)?)?;
+    }
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+impl MetricsStore for FileMetricsStore {
+    fn get(&self, slug: &str) -> PostMetrics {
+        let metrics = self.metrics.lock().unwrap();
+        metrics.get(slug).cloned().unwrap_or_default()
+    }
+
+    fn increment_view(&self, slug: &str) {
+        self.record(MetricsEvent::View {
+            slug: slug.to_string(),
+        });
+    }
+
+    fn record_vote(&self, slug: &str, is_like: bool) {
+        self.record(MetricsEvent::vote(slug, is_like));
+    }
+}
+
+/// Build the metrics store selected by the environment.
+///
+/// `BLOG_METRICS_STORE` chooses the backend (`file` by default, or `memory`), and
+/// `BLOG_METRICS_PATH` sets the log location for the file backend.
+pub fn metrics_store_from_env() -> io::Result<Box<dyn MetricsStore>> {
+    let backend = std::env::var("BLOG_METRICS_STORE").unwrap_or_else(|_| "file".to_string());
+
+    match backend.as_str() {
+        "memory" => Ok(Box::new(MemoryMetricsStore::default())),
+        "file" => {
+            let path = std::env::var("BLOG_METRICS_PATH")
+                .unwrap_or_else(|_| "data/metrics.log".to_string());
+            Ok(Box::new(FileMetricsStore::open(path)?))
+        }
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown BLOG_METRICS_STORE backend: {}", other),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reopening_compacts_the_log_and_keeps_totals() {
+        let dir = std::env::temp_dir().join(format!("blog-metrics-{}", std::process::id()));
+        let path = dir.join("metrics.log");
+        let _ = fs::remove_dir_all(&dir);
+
+        {
+            let store = FileMetricsStore::open(&path).unwrap();
+            store.increment_view("a");
+            store.increment_view("a");
+            store.record_vote("a", true);
+            store.record_vote("b", false);
+        }
+        let store = FileMetricsStore::open(&path).unwrap();
+        store.increment_view("b");
+
+        let a = store.get("a");
+        assert_eq!((a.views, a.likes, a.dislikes), (2, 1, 0));
+        let b = store.get("b");
+        assert_eq!((b.views, b.likes, b.dislikes), (1, 0, 1));
+        // One totals line per slug plus the event recorded after reopening
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+        drop(store);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,121 @@
+use super::markdown::Heading;
+use leptos::*;
+
+// Distance from the top of the viewport at which a heading counts as the current section
+#[cfg(feature = "hydrate")]
+const SCROLL_SPY_OFFSET: f64 = 96.0;
+
+fn flatten(headings: &[Heading], out: &mut Vec<Heading>) {
+    for heading in headings {
+        out.push(heading.clone());
+        flatten(&heading.children, out);
+    }
+}
+
+// Sticky, collapsible list of the post's headings, highlighting the section being read
+#[component]
+pub fn TableOfContents(headings: Vec<Heading>) -> impl IntoView {
+    let mut flat = Vec::new();
+    flatten(&headings, &mut flat);
+    let min_level = flat.iter().map(|heading| heading.level).min().unwrap_or(1);
+
+    let (is_open, set_is_open) = create_signal(false);
+    #[cfg_attr(not(feature = "hydrate"), allow(unused_variables))]
+    let (active_id, set_active_id) = create_signal(None::<String>);
+
+    // Scroll-spy: the active heading is the last one scrolled past the offset
+    #[cfg(feature = "hydrate")]
+    {
+        let ids: Vec<String> = flat.iter().map(|heading| heading.id.clone()).collect();
+        let update_active = move || {
+            let current = ids
+                .iter()
+                .take_while(|id| {
+                    document().get_element_by_id(id).is_some_and(|element| {
+                        element.get_bounding_client_rect().top() <= SCROLL_SPY_OFFSET
+                    })
+                })
+                .last()
+                .cloned();
+            if active_id.get_untracked() != current {
+                set_active_id.set(current);
+            }
+        };
+
+        create_effect({
+            let update_active = update_active.clone();
+            move |_| update_active()
+        });
+        let handle = window_event_listener(ev::scroll, move |_| update_active());
+        on_cleanup(move || handle.remove());
+    }
+
+    let current_title = {
+        let flat = flat.clone();
+        move || {
+            active_id
+                .get()
+                .and_then(|id| flat.iter().find(|heading| heading.id == id))
+                .map(|heading| heading.text.clone())
+        }
+    };
+
+    view! {
+        <nav
+            aria-label="Table of contents"
+            class="sticky top-0 z-40 -mx-4 sm:mx-0 mb-8 sm:mb-10 bg-background/95 backdrop-blur-sm border-b-2 sm:border-2 border-border"
+        >
+            <button
+                class="w-full flex items-center justify-between gap-3 px-4 py-2 text-left text-sm sm:text-base text-foreground hover:bg-accent transition-colors cursor-pointer"
+                aria-expanded=move || is_open.get().to_string()
+                on:click=move |_| set_is_open.update(|open| *open = !*open)
+            >
+                <span class="flex items-center gap-2 min-w-0">
+                    <svg class="w-4 h-4 flex-shrink-0" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M4 6h16M4 12h10M4 18h13"></path>
+                    </svg>
+                    <span class="font-semibold">"Contents"</span>
+                    {move || current_title().map(|title| view! {
+                        <span class="text-muted-foreground truncate">"· "{title}</span>
+                    })}
+                </span>
+                <svg
+                    class=move || format!("w-4 h-4 flex-shrink-0 transition-transform {}", if is_open.get() { "rotate-180" } else { "" })
+                    fill="none" stroke="currentColor" viewBox="0 0 24 24"
+                >
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M19 9l-7 7-7-7"></path>
+                </svg>
+            </button>
+
+            <Show when=move || is_open.get() fallback=|| ()>
+                <ol class="max-h-[60vh] overflow-y-auto px-4 pb-3 space-y-1 text-sm">
+                    {flat.iter().map(|heading| {
+                        let id = heading.id.clone();
+                        let href = format!("#{}", heading.id);
+                        // Indent relative to the shallowest heading level
+                        let depth = heading.level - min_level;
+                        let indent = format!("padding-left: {}rem", depth as f32 * 0.75);
+                        view! {
+                            <li style=indent>
+                                <a
+                                    href=href
+                                    class=move || format!(
+                                        "block py-0.5 truncate transition-colors {}",
+                                        if active_id.get().as_deref() == Some(id.as_str()) {
+                                            "text-foreground font-semibold"
+                                        } else {
+                                            "text-muted-foreground hover:text-foreground"
+                                        }
+                                    )
+                                    on:click=move |_| set_is_open.set(false)
+                                >
+                                    {heading.text.clone()}
+                                </a>
+                            </li>
+                        }
+                    }).collect_view()}
+                </ol>
+            </Show>
+        </nav>
+    }
+}
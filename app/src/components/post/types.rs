@@ -1,15 +1,90 @@
+use super::markdown::ParsedMarkdown;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct BlogPost {
     pub slug: String,
     pub title: String,
     pub date: DateTime<Utc>,
+    // Last significant edit, from the optional `updated` frontmatter field
+    pub updated: Option<DateTime<Utc>>,
     pub excerpt: String,
     pub content: String,
     pub tags: Vec<String>,
+    pub status: PostStatus,
+    // Set by `trusted: true` in frontmatter; raw HTML in other posts is sanitized
+    #[serde(default)]
+    pub trusted: bool,
+    // Name of the multi-part series the post belongs to, and its place in it
+    #[serde(default)]
+    pub series: Option<String>,
+    #[serde(default)]
+    pub series_order: Option<u32>,
+    // Prose words and fenced code lines, counted separately at parse time
+    pub word_count: usize,
+    pub code_lines: usize,
+    pub reading_time_minutes: u32,
     pub metrics: PostMetrics,
+    // The content rendered on the server, only filled for a single post
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendered: Option<ParsedMarkdown>,
+}
+
+impl BlogPost {
+    // When the post last changed, for feeds and sitemaps
+    pub fn last_modified(&self) -> DateTime<Utc> {
+        self.updated.unwrap_or(self.date).max(self.date)
+    }
+
+    // Reachable by direct link: anything but drafts, once its date has passed
+    pub fn is_published(&self, now: DateTime<Utc>) -> bool {
+        self.status != PostStatus::Draft && self.date <= now
+    }
+
+    // Shown in listings, feeds, search and the sitemap
+    pub fn is_listed(&self, now: DateTime<Utc>) -> bool {
+        self.status == PostStatus::Published && self.date <= now
+    }
+}
+
+// Stable FNV-1a hash as hex, for cache keys and generated file names
+pub fn content_hash(data: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in data {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PostStatus {
+    Draft,
+    #[default]
+    Published,
+    // Reachable by URL but left out of every listing
+    Unlisted,
+}
+
+impl PostStatus {
+    pub fn from_str(status: &str) -> Option<Self> {
+        match status.trim().to_lowercase().as_str() {
+            "draft" => Some(Self::Draft),
+            "published" => Some(Self::Published),
+            "unlisted" => Some(Self::Unlisted),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Published => "published",
+            Self::Unlisted => "unlisted",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -19,6 +94,274 @@ pub struct PostMetrics {
     pub dislikes: u64,
 }
 
+// What a post card or link needs, without the Markdown body
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PostSummary {
+    pub slug: String,
+    pub title: String,
+    pub date: DateTime<Utc>,
+    pub excerpt: String,
+    pub reading_time_minutes: u32,
+    pub views: u64,
+}
+
+impl From<&BlogPost> for PostSummary {
+    fn from(post: &BlogPost) -> Self {
+        Self {
+            slug: post.slug.clone(),
+            title: post.title.clone(),
+            date: post.date,
+            excerpt: post.excerpt.clone(),
+            reading_time_minutes: post.reading_time_minutes,
+            views: post.metrics.views,
+        }
+    }
+}
+
+impl From<BlogPost> for PostSummary {
+    fn from(post: BlogPost) -> Self {
+        Self::from(&post)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchHit {
+    pub post: PostSummary,
+    pub score: f32,
+    // Escaped body excerpt with matching words wrapped in <mark>
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+// Count posts per tag, most used first; tags differing only in case are merged
+#[cfg(feature = "ssr")]
+pub fn count_tags(posts: &[BlogPost]) -> Vec<TagCount> {
+    let mut counts: Vec<TagCount> = Vec::new();
+    for tag in posts.iter().flat_map(|post| post.tags.iter()) {
+        match counts.iter_mut().find(|c| c.tag.eq_ignore_ascii_case(tag)) {
+            Some(existing) => existing.count += 1,
+            None => counts.push(TagCount {
+                tag: tag.clone(),
+                count: 1,
+            }),
+        }
+    }
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    counts
+}
+
+// A post as listed in the archive
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArchivePost {
+    pub slug: String,
+    pub title: String,
+    pub date: DateTime<Utc>,
+    // Day of the month in the blog's timezone
+    pub day: u32,
+}
+
+// Listed posts published in one month, newest first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArchiveMonth {
+    // 1 to 12
+    pub month: u32,
+    pub posts: Vec<ArchivePost>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArchiveYear {
+    pub year: i32,
+    // Newest month first, only months with posts
+    pub months: Vec<ArchiveMonth>,
+}
+
+impl ArchiveYear {
+    pub fn count(&self) -> usize {
+        self.months.iter().map(|month| month.posts.len()).sum()
+    }
+}
+
+// Group posts by the year and month they were published in `BLOG_TIMEZONE`, newest first.
+// `posts` must already be sorted newest first, as `get_all_posts` returns them
+#[cfg(feature = "ssr")]
+pub fn group_archive(posts: Vec<BlogPost>) -> Vec<ArchiveYear> {
+    group_archive_in(posts, default_timezone())
+}
+
+#[cfg(feature = "ssr")]
+fn group_archive_in(posts: Vec<BlogPost>, timezone: chrono_tz::Tz) -> Vec<ArchiveYear> {
+    use chrono::Datelike;
+
+    let mut years: Vec<ArchiveYear> = Vec::new();
+    for post in posts {
+        let local = post.date.with_timezone(&timezone);
+        let (year, month) = (local.year(), local.month());
+        if years.last().map(|y| y.year) != Some(year) {
+            years.push(ArchiveYear {
+                year,
+                months: Vec::new(),
+            });
+        }
+        let months = &mut years.last_mut().unwrap().months;
+        if months.last().map(|m| m.month) != Some(month) {
+            months.push(ArchiveMonth {
+                month,
+                posts: Vec::new(),
+            });
+        }
+        months.last_mut().unwrap().posts.push(ArchivePost {
+            slug: post.slug,
+            title: post.title,
+            date: post.date,
+            day: local.day(),
+        });
+    }
+    years
+}
+
+// Posts shown on each page of the home listing
+pub const POSTS_PER_PAGE: usize = 10;
+
+// One page of the post listing, newest first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PagedPosts {
+    pub posts: Vec<PostSummary>,
+    // Page the posts start on, counting from 1
+    pub page: usize,
+    pub total_pages: usize,
+    pub total_posts: usize,
+    // Slug of the last post when more follow, to continue after it
+    pub next_cursor: Option<String>,
+}
+
+// Cut a page out of `posts`. A `cursor` takes precedence over `page`: the page then starts
+// right after that slug, so posts published in the meantime don't shift what comes next
+#[cfg(feature = "ssr")]
+pub fn paginate(posts: &[BlogPost], page: usize, cursor: Option<&str>) -> PagedPosts {
+    let total_posts = posts.len();
+    let total_pages = total_posts.div_ceil(POSTS_PER_PAGE).max(1);
+    let start = cursor
+        .and_then(|cursor| posts.iter().position(|post| post.slug == cursor))
+        .map_or_else(
+            || (page.clamp(1, total_pages) - 1) * POSTS_PER_PAGE,
+            |position| position + 1,
+        );
+    let end = (start + POSTS_PER_PAGE).min(total_posts);
+    let next_cursor = (end < total_posts).then(|| posts[end - 1].slug.clone());
+
+    PagedPosts {
+        posts: posts
+            .iter()
+            .skip(start)
+            .take(POSTS_PER_PAGE)
+            .map(PostSummary::from)
+            .collect(),
+        page: start / POSTS_PER_PAGE + 1,
+        total_pages,
+        total_posts,
+        next_cursor,
+    }
+}
+
+// What to read after a post: the posts published either side of it and similar ones
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct RelatedPosts {
+    // The next older and next newer listed post
+    pub previous: Option<PostSummary>,
+    pub next: Option<PostSummary>,
+    pub related: Vec<PostSummary>,
+}
+
+// The parts of a multi-part series, in reading order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Series {
+    pub name: String,
+    // URL segment, the slugified name
+    pub slug: String,
+    pub parts: Vec<PostSummary>,
+}
+
+impl Series {
+    // Position of a post in the series, starting at 0
+    pub fn position(&self, slug: &str) -> Option<usize> {
+        self.parts.iter().position(|part| part.slug == slug)
+    }
+}
+
+// Link to the index page of a series
+pub fn series_href(name: &str) -> String {
+    format!("/series/{}", super::markdown::slugify(name))
+}
+
+// Group posts into series, most recently continued first. Parts are ordered by
+// `series_order`, then by date; names differing only in case or punctuation are merged
+#[cfg(feature = "ssr")]
+pub fn collect_series<'a>(posts: impl IntoIterator<Item = &'a BlogPost>) -> Vec<Series> {
+    use super::markdown::slugify;
+
+    let mut groups: Vec<(String, String, Vec<&BlogPost>)> = Vec::new();
+    for post in posts {
+        let Some(name) = &post.series else {
+            continue;
+        };
+        let slug = slugify(name);
+        match groups.iter_mut().find(|(_, s, _)| *s == slug) {
+            Some((_, _, parts)) => parts.push(post),
+            None => groups.push((name.clone(), slug, vec![post])),
+        }
+    }
+
+    let mut series: Vec<Series> = groups
+        .into_iter()
+        .map(|(name, slug, mut parts)| {
+            parts.sort_by_key(|part| (part.series_order.unwrap_or(u32::MAX), part.date));
+            Series {
+                name,
+                slug,
+                parts: parts.into_iter().map(PostSummary::from).collect(),
+            }
+        })
+        .collect();
+    sort_series(&mut series);
+    series
+}
+
+// Most recently continued first
+#[cfg(feature = "ssr")]
+pub(crate) fn sort_series(series: &mut [Series]) {
+    series.sort_by_key(|s| std::cmp::Reverse(s.parts.iter().map(|part| part.date).max()));
+}
+
+/// Why a post file was left out of the index.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PostParseError {
+    pub file: String,
+    // Frontmatter field at fault, if the problem is specific to one
+    pub field: Option<String>,
+    pub reason: String,
+    pub line: Option<usize>,
+}
+
+impl std::fmt::Display for PostParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if let Some(field) = &self.field {
+            write!(f, ": `{}`", field)?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+impl std::error::Error for PostParseError {}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostMeta {
     pub title: String,
@@ -29,121 +372,699 @@ pub struct PostMeta {
 
 // Server-side functions for handling posts and metrics
 #[cfg(feature = "ssr")]
-use std::collections::HashMap;
+use super::index::post_index;
 #[cfg(feature = "ssr")]
-use std::sync::Mutex;
+use super::store::{MemoryMetricsStore, MetricsStore};
 
+// Directory the post index is built from
 #[cfg(feature = "ssr")]
-pub async fn get_all_posts() -> Result<Vec<BlogPost>, std::io::Error> {
-    use std::path::Path;
-    use tokio::fs;
-
-    let posts_dir = Path::new("app/posts");
-    let mut posts = Vec::new();
-
-    if posts_dir.exists() {
-        let mut entries = fs::read_dir(posts_dir).await?;
-
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("md") {
-                if let Ok(content) = fs::read_to_string(&path).await {
-                    if let Some(post) = parse_post(&content, &path) {
-                        posts.push(post);
-                    }
-                }
-            }
-        }
+pub const POSTS_DIR: &str = "app/posts";
+
+// Set BLOG_PREVIEW_DRAFTS=1 in development to see drafts, unlisted and scheduled posts
+#[cfg(feature = "ssr")]
+pub fn preview_drafts() -> bool {
+    static PREVIEW: std::sync::LazyLock<bool> = std::sync::LazyLock::new(|| {
+        matches!(
+            std::env::var("BLOG_PREVIEW_DRAFTS").as_deref(),
+            Ok("1") | Ok("true")
+        )
+    });
+    *PREVIEW
+}
+
+// Timezone for frontmatter dates that don't carry an offset, from BLOG_TIMEZONE (e.g. Europe/Vienna)
+#[cfg(feature = "ssr")]
+pub fn default_timezone() -> chrono_tz::Tz {
+    static TIMEZONE: std::sync::LazyLock<chrono_tz::Tz> =
+        std::sync::LazyLock::new(|| match std::env::var("BLOG_TIMEZONE") {
+            Ok(name) if !name.trim().is_empty() => name.trim().parse().unwrap_or_else(|e| {
+                tracing::warn!("Invalid BLOG_TIMEZONE {:?}, using UTC: {}", name, e);
+                chrono_tz::UTC
+            }),
+            _ => chrono_tz::UTC,
+        });
+    *TIMEZONE
+}
+
+/// Parse a frontmatter date.
+///
+/// Accepts RFC 3339, YAML timestamps (`2024-05-01 09:30:00 +2`), datetimes without an
+/// offset and plain dates; the last two are taken to be in `timezone`, plain dates at midnight.
+#[cfg(feature = "ssr")]
+pub fn parse_date(value: &str, timezone: chrono_tz::Tz) -> Option<DateTime<Utc>> {
+    use chrono::{NaiveDate, NaiveDateTime, TimeZone};
+
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(&value.replacen(['t', ' '], "T", 1)) {
+        return Some(date.with_timezone(&Utc));
     }
 
-    // Sort posts by date (newest first)
-    posts.sort_by(|a, b| b.date.cmp(&a.date));
+    // YAML allows a space before the offset and single-digit hours in it
+    let (local, offset) = split_offset(value);
+    let local = local.trim_end().replacen(['t', 'T'], " ", 1);
+
+    let naive = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&local, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(&local, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })?;
 
-    Ok(posts)
+    match offset {
+        Some(offset) => offset
+            .from_local_datetime(&naive)
+            .single()
+            .map(|d| d.with_timezone(&Utc)),
+        // Ambiguous local times (DST fall-back) resolve to the earlier instant
+        None => timezone
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|d| d.with_timezone(&Utc)),
+    }
 }
 
+// Split a trailing `Z`, `+2`, `-05` or `+05:30` off a datetime
 #[cfg(feature = "ssr")]
-pub async fn get_post_by_slug(slug: &str) -> Result<Option<BlogPost>, std::io::Error> {
-    use std::path::Path;
-    use tokio::fs;
+fn split_offset(value: &str) -> (&str, Option<chrono::FixedOffset>) {
+    use chrono::FixedOffset;
 
-    let post_path = Path::new("app/posts").join(format!("{}.md", slug));
+    if let Some(local) = value.strip_suffix(['Z', 'z']) {
+        return (local, FixedOffset::east_opt(0));
+    }
 
-    if post_path.exists() {
-        let content = fs::read_to_string(&post_path).await?;
-        Ok(parse_post(&content, &post_path))
+    // Only look past the time part so the date's own dashes aren't mistaken for an offset
+    let Some(time_start) = value.find([' ', 't', 'T']) else {
+        return (value, None);
+    };
+    let Some(sign_index) = value[time_start..]
+        .rfind(['+', '-'])
+        .map(|i| i + time_start)
+    else {
+        return (value, None);
+    };
+
+    let sign = if value[sign_index..].starts_with('-') {
+        -1
     } else {
-        Ok(None)
+        1
+    };
+    let mut parts = value[sign_index + 1..].splitn(2, ':');
+    let hours: i32 = match parts.next().and_then(|h| h.parse().ok()) {
+        Some(hours) => hours,
+        None => return (value, None),
+    };
+    let minutes: i32 = match parts.next() {
+        Some(m) => match m.parse() {
+            Ok(minutes) => minutes,
+            Err(_) => return (value, None),
+        },
+        None => 0,
+    };
+
+    let offset = FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60));
+    match offset {
+        Some(offset) => (&value[..sign_index], Some(offset)),
+        None => (value, None),
     }
 }
 
 #[cfg(feature = "ssr")]
-fn parse_post(content: &str, path: &std::path::Path) -> Option<BlogPost> {
+pub async fn get_all_posts() -> Result<Vec<BlogPost>, std::io::Error> {
+    let index = post_index();
+    let index = index.read().unwrap();
+    Ok(index.posts())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn search_all_posts(query: &str) -> Result<Vec<SearchHit>, std::io::Error> {
+    let index = post_index();
+    let index = index.read().unwrap();
+    Ok(index.search(query))
+}
+
+#[cfg(feature = "ssr")]
+pub async fn find_related_posts(slug: &str) -> Result<RelatedPosts, std::io::Error> {
+    let index = post_index();
+    let index = index.read().unwrap();
+    Ok(index.related(slug))
+}
+
+#[cfg(feature = "ssr")]
+pub async fn list_series() -> Result<Vec<Series>, std::io::Error> {
+    let index = post_index();
+    let index = index.read().unwrap();
+    Ok(index.series())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_post_by_slug(slug: &str) -> Result<Option<BlogPost>, std::io::Error> {
+    let index = post_index();
+    let index = index.read().unwrap();
+    Ok(index.get(slug))
+}
+
+#[cfg(feature = "ssr")]
+pub(crate) fn parse_post(
+    content: &str,
+    path: &std::path::Path,
+) -> Result<BlogPost, PostParseError> {
     use gray_matter::engine::YAML;
-    use gray_matter::Matter;
+    use gray_matter::{Matter, Pod};
+
+    let error = |field: Option<&str>, reason: String| PostParseError {
+        file: path.display().to_string(),
+        field: field.map(str::to_string),
+        reason,
+        line: field.map_or(Some(1), |f| frontmatter_line(content, f)),
+    };
 
     let matter = Matter::<YAML>::new();
     let result = matter.parse(content);
 
     // Parse the frontmatter directly from the Pod
-    let data = result.data?;
-    let map = data.as_hashmap().ok()?;
+    let data = result
+        .data
+        .ok_or_else(|| error(None, "missing or invalid YAML frontmatter".to_string()))?;
+    let map = data
+        .as_hashmap()
+        .map_err(|_| error(None, "frontmatter is not a key/value mapping".to_string()))?;
+
+    let string_field = |field: &str| -> Result<Option<String>, PostParseError> {
+        match map.get(field) {
+            None | Some(Pod::Null) => Ok(None),
+            Some(value) => value
+                .as_string()
+                .map(Some)
+                .map_err(|_| error(Some(field), "expected a string".to_string())),
+        }
+    };
+    let required = |field: &str| -> Result<String, PostParseError> {
+        string_field(field)?.ok_or_else(|| error(Some(field), "missing required field".to_string()))
+    };
+    let timestamp = |field: &str, value: &str| -> Result<DateTime<Utc>, PostParseError> {
+        parse_date(value, default_timezone()).ok_or_else(|| {
+            error(
+                Some(field),
+                format!(
+                    "`{}` is not a date like 2024-05-01, 2024-05-01 09:30 or 2024-05-01T09:30:00Z",
+                    value
+                ),
+            )
+        })
+    };
 
-    let title = map.get("title")?.as_string().ok()?;
-    let date_str = map.get("date")?.as_string().ok()?;
+    let title = required("title")?;
+    let date = timestamp("date", &required("date")?)?;
 
-    let date = DateTime::parse_from_rfc3339(&date_str)
-        .ok()?
-        .with_timezone(&Utc);
+    let updated = string_field("updated")?
+        .map(|value| timestamp("updated", &value))
+        .transpose()?;
 
-    let excerpt = map
-        .get("excerpt")
-        .and_then(|v| v.as_string().ok())
-        .unwrap_or_default();
+    let excerpt = string_field("excerpt")?.unwrap_or_default();
+
+    // YAML reads `[rust, 2024]` as a string and a number; both are kept as tags
+    let tags = match map.get("tags") {
+        None | Some(Pod::Null) => Vec::new(),
+        Some(value) => value
+            .as_vec()
+            .ok()
+            .and_then(|vec| {
+                vec.iter()
+                    .map(|tag| match tag {
+                        Pod::String(tag) => Some(tag.clone()),
+                        Pod::Integer(tag) => Some(tag.to_string()),
+                        Pod::Float(tag) => Some(tag.to_string()),
+                        Pod::Boolean(tag) => Some(tag.to_string()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .ok_or_else(|| error(Some("tags"), "expected a list of strings".to_string()))?,
+    };
+
+    let status = match string_field("status")? {
+        Some(status) => PostStatus::from_str(&status).ok_or_else(|| {
+            error(
+                Some("status"),
+                format!(
+                    "unknown status `{}`, expected draft, published or unlisted",
+                    status
+                ),
+            )
+        })?,
+        None => PostStatus::Published,
+    };
+
+    let series = string_field("series")?.filter(|series| !series.trim().is_empty());
+    let series_order = match map.get("series_order") {
+        None | Some(Pod::Null) => None,
+        Some(value) => Some(
+            value
+                .as_i64()
+                .ok()
+                .and_then(|order| u32::try_from(order).ok())
+                .filter(|order| *order > 0)
+                .ok_or_else(|| {
+                    error(
+                        Some("series_order"),
+                        "expected a positive whole number".to_string(),
+                    )
+                })?,
+        ),
+    };
+    if series_order.is_some() && series.is_none() {
+        return Err(error(
+            Some("series_order"),
+            "`series_order` needs a `series` to order within".to_string(),
+        ));
+    }
 
-    let tags = map
-        .get("tags")
-        .and_then(|v| v.as_vec().ok())
-        .map(|vec| vec.iter().filter_map(|v| v.as_string().ok()).collect())
-        .unwrap_or_default();
+    let trusted = match map.get("trusted") {
+        None | Some(Pod::Null) => false,
+        Some(value) => value
+            .as_bool()
+            .map_err(|_| error(Some("trusted"), "expected true or false".to_string()))?,
+    };
 
-    let slug = path.file_stem()?.to_str()?.to_string();
+    let slug = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| error(None, "file name is not valid UTF-8".to_string()))?
+        .to_string();
 
-    Some(BlogPost {
-        slug: slug.clone(),
+    let (word_count, code_lines) = count_words(&result.content);
+
+    Ok(BlogPost {
+        slug,
         title,
         date,
+        updated,
         excerpt,
         content: result.content,
         tags,
-        metrics: get_post_metrics(&slug),
+        status,
+        trusted,
+        series,
+        series_order,
+        word_count,
+        code_lines,
+        reading_time_minutes: reading_time_minutes(word_count, code_lines),
+        metrics: PostMetrics::default(),
+        rendered: None,
     })
 }
 
 #[cfg(feature = "ssr")]
-static METRICS_STORE: std::sync::LazyLock<Mutex<HashMap<String, PostMetrics>>> =
-    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+const WORDS_PER_MINUTE: f32 = 200.0;
+// Code is read more slowly than prose
+#[cfg(feature = "ssr")]
+const CODE_LINES_PER_MINUTE: f32 = 30.0;
+
+// Words of rendered text, and non-blank lines of code blocks. Counted from parser events so
+// link targets, image paths and raw HTML aren't mistaken for prose
+#[cfg(feature = "ssr")]
+fn count_words(content: &str) -> (usize, usize) {
+    use pulldown_cmark::{Event, Options, Parser, Tag};
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut prose = String::new();
+    let mut code_lines = 0;
+    let mut in_code = false;
+    // Alt text is never read inline
+    let mut image_depth = 0;
+
+    for event in Parser::new_ext(content, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(Tag::CodeBlock(_)) => in_code = false,
+            Event::Start(Tag::Image(..)) => image_depth += 1,
+            Event::End(Tag::Image(..)) => image_depth -= 1,
+            Event::Text(text) if in_code => {
+                code_lines += text.lines().filter(|line| !line.trim().is_empty()).count();
+            }
+            Event::Text(text) | Event::Code(text) if image_depth == 0 => prose.push_str(&text),
+            Event::SoftBreak | Event::HardBreak => prose.push(' '),
+            // Inline markup can split a word across events; anything else ends one
+            Event::End(Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link(..)) => {}
+            Event::End(_) => prose.push(' '),
+            _ => {}
+        }
+    }
+
+    let words = prose
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count();
+    (words, code_lines)
+}
+
+#[cfg(feature = "ssr")]
+fn reading_time_minutes(words: usize, code_lines: usize) -> u32 {
+    let minutes = words as f32 / WORDS_PER_MINUTE + code_lines as f32 / CODE_LINES_PER_MINUTE;
+    (minutes.ceil() as u32).max(1)
+}
+
+// 1-based line of `field:` inside the leading frontmatter block
+#[cfg(feature = "ssr")]
+fn frontmatter_line(content: &str, field: &str) -> Option<usize> {
+    let prefix = format!("{}:", field);
+    content
+        .lines()
+        .enumerate()
+        .skip(1)
+        .take_while(|(_, line)| line.trim_end() != "---")
+        .find(|(_, line)| line.starts_with(&prefix))
+        .map(|(index, _)| index + 1)
+}
+
+#[cfg(feature = "ssr")]
+static METRICS_STORE: std::sync::OnceLock<Box<dyn MetricsStore>> = std::sync::OnceLock::new();
+
+// Install the metrics backend; must be called before the first metrics access
+#[cfg(feature = "ssr")]
+pub fn set_metrics_store(store: Box<dyn MetricsStore>) {
+    if METRICS_STORE.set(store).is_err() {
+        tracing::warn!("Metrics store already initialized, ignoring replacement");
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn metrics_store() -> &'static dyn MetricsStore {
+    METRICS_STORE
+        .get_or_init(|| Box::new(MemoryMetricsStore::default()))
+        .as_ref()
+}
 
 #[cfg(feature = "ssr")]
 pub fn get_post_metrics(slug: &str) -> PostMetrics {
-    let store = METRICS_STORE.lock().unwrap();
-    store.get(slug).cloned().unwrap_or_default()
+    metrics_store().get(slug)
 }
 
 #[cfg(feature = "ssr")]
 pub fn increment_view(slug: &str) {
-    let mut store = METRICS_STORE.lock().unwrap();
-    let metrics = store.entry(slug.to_string()).or_default();
-    metrics.views += 1;
+    metrics_store().increment_view(slug);
 }
 
 #[cfg(feature = "ssr")]
 pub fn update_vote(slug: &str, is_like: bool) {
-    let mut store = METRICS_STORE.lock().unwrap();
-    let metrics = store.entry(slug.to_string()).or_default();
-    if is_like {
-        metrics.likes += 1;
-    } else {
-        metrics.dislikes += 1;
+    metrics_store().record_vote(slug, is_like);
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::path::Path;
+
+    fn parse(source: &str) -> Result<BlogPost, PostParseError> {
+        parse_post(source, Path::new("posts/example.md"))
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_frontmatter_line() {
+        let error = parse("---\ntitle: Hello\ndate: someday\n---\n\nBody\n").unwrap_err();
+        assert_eq!(error.file, "posts/example.md");
+        assert_eq!(error.field.as_deref(), Some("date"));
+        assert_eq!(error.line, Some(3));
+
+        let error = parse("---\ntitle: Hello\ndate: 2024-05-01\nstatus: gone\n---\n").unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("status"));
+        assert_eq!(error.line, Some(4));
+    }
+
+    #[test]
+    fn missing_fields_and_frontmatter_fall_back_to_the_first_line() {
+        let error = parse("---\ndate: 2024-05-01\n---\n").unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("title"));
+        assert_eq!(error.line, None);
+
+        let error = parse("Just a body\n").unwrap_err();
+        assert_eq!(error.field, None);
+        assert_eq!(error.line, Some(1));
+    }
+
+    #[test]
+    fn error_display_includes_file_line_and_field() {
+        let error = parse("---\ntitle: Hello\ndate: someday\n---\n").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("posts/example.md:3: `date`: `someday` is not a date"));
+    }
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn dates_in_every_accepted_format() {
+        let tz = chrono_tz::UTC;
+        for (value, expected) in [
+            ("2024-05-01T09:30:00Z", "2024-05-01T09:30:00Z"),
+            ("2024-05-01t09:30:00+02:00", "2024-05-01T07:30:00Z"),
+            ("2024-05-01 09:30:00 +2", "2024-05-01T07:30:00Z"),
+            ("2024-05-01 09:30:00.5 -05", "2024-05-01T14:30:00.5Z"),
+            ("2024-05-01 09:30", "2024-05-01T09:30:00Z"),
+            ("2024-05-01T09:30:00", "2024-05-01T09:30:00Z"),
+            ("2024-05-01", "2024-05-01T00:00:00Z"),
+            ("  2024-05-01  ", "2024-05-01T00:00:00Z"),
+        ] {
+            assert_eq!(parse_date(value, tz), Some(utc(expected)), "{}", value);
+        }
+    }
+
+    #[test]
+    fn dates_without_an_offset_use_the_configured_timezone() {
+        let vienna: chrono_tz::Tz = "Europe/Vienna".parse().unwrap();
+        assert_eq!(
+            parse_date("2024-01-01", vienna),
+            Some(utc("2023-12-31T23:00:00Z"))
+        );
+        assert_eq!(
+            parse_date("2024-07-01 12:00", vienna),
+            Some(utc("2024-07-01T10:00:00Z"))
+        );
+        // An explicit offset wins over the timezone
+        assert_eq!(
+            parse_date("2024-07-01 12:00 Z", vienna),
+            Some(utc("2024-07-01T12:00:00Z"))
+        );
+        // The repeated hour when clocks go back resolves to the earlier instant
+        assert_eq!(
+            parse_date("2024-10-27 02:30", vienna),
+            Some(utc("2024-10-27T00:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn invalid_dates_are_rejected() {
+        for value in ["", "someday", "2024-13-01", "2024-02-30", "01/05/2024"] {
+            assert_eq!(parse_date(value, chrono_tz::UTC), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn offsets_are_only_split_from_the_time_part() {
+        let hours = |h: i32| chrono::FixedOffset::east_opt(h * 3600);
+        assert_eq!(split_offset("2024-05-01"), ("2024-05-01", None));
+        assert_eq!(
+            split_offset("2024-05-01 09:30 +2"),
+            ("2024-05-01 09:30 ", hours(2))
+        );
+        assert_eq!(
+            split_offset("2024-05-01T09:30-05:30"),
+            (
+                "2024-05-01T09:30",
+                chrono::FixedOffset::east_opt(-(5 * 3600 + 30 * 60))
+            )
+        );
+        assert_eq!(
+            split_offset("2024-05-01T09:30Z"),
+            ("2024-05-01T09:30", hours(0))
+        );
+        assert_eq!(split_offset("2024-05-01 09:30"), ("2024-05-01 09:30", None));
+        assert_eq!(
+            split_offset("2024-05-01 09:30 +x"),
+            ("2024-05-01 09:30 +x", None)
+        );
+    }
+
+    #[test]
+    fn words_come_from_rendered_text_only() {
+        let content = "# A title\n\nSee [the docs](https://example.com/some/long/path) and \
+                       ![a diagram](/assets/diagram.png).\n\n<div class=\"note\">\n\n\
+                       Some *emph*asis, `inline code` and a list:\n\n- one\n- two\n";
+        // A title / See the docs and . / Some emphasis, inline code and a list: / one / two
+        assert_eq!(count_words(content), (15, 0));
+    }
+
+    #[test]
+    fn code_blocks_count_lines_not_words() {
+        let content = "Intro text\n\n```rust\nfn main() {\n\n    println!(\"hi\");\n}\n```\n\n    indented code\n\nOutro\n";
+        assert_eq!(count_words(content), (3, 4));
+    }
+
+    #[test]
+    fn reading_time_rounds_up_and_is_at_least_a_minute() {
+        assert_eq!(reading_time_minutes(0, 0), 1);
+        assert_eq!(reading_time_minutes(200, 0), 1);
+        assert_eq!(reading_time_minutes(201, 0), 2);
+        assert_eq!(reading_time_minutes(200, 30), 2);
+        assert_eq!(reading_time_minutes(1000, 60), 7);
+    }
+
+    #[test]
+    fn scalar_tags_are_kept_as_strings() {
+        let post =
+            parse("---\ntitle: Hello\ndate: 2024-05-01\ntags: [rust, 2024, true]\n---\n").unwrap();
+        assert_eq!(post.tags, ["rust", "2024", "true"]);
+
+        let error =
+            parse("---\ntitle: Hello\ndate: 2024-05-01\ntags: [[nested]]\n---\n").unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("tags"));
+    }
+
+    #[test]
+    fn series_parts_follow_their_order_then_date() {
+        let part = |slug: &str, series: &str, order: Option<u32>, day: u32| BlogPost {
+            slug: slug.to_string(),
+            title: slug.to_uppercase(),
+            date: Utc.with_ymd_and_hms(2024, 5, day, 0, 0, 0).unwrap(),
+            series: Some(series.to_string()),
+            series_order: order,
+            ..BlogPost::default()
+        };
+        let posts = [
+            part("b", "Building a Blog", None, 3),
+            part("a", "building a blog!", None, 1),
+            part("intro", "Building a Blog", Some(1), 9),
+            part("other", "Other", None, 2),
+            BlogPost::default(),
+        ];
+
+        let series = collect_series(&posts);
+        let summary: Vec<(&str, Vec<&str>)> = series
+            .iter()
+            .map(|s| {
+                let parts = s.parts.iter().map(|part| part.slug.as_str()).collect();
+                (s.slug.as_str(), parts)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("building-a-blog", vec!["intro", "a", "b"]),
+                ("other", vec!["other"]),
+            ]
+        );
+        assert_eq!(series[0].name, "Building a Blog");
+        assert_eq!(series[0].position("a"), Some(1));
+    }
+
+    fn numbered_posts(count: usize) -> Vec<BlogPost> {
+        (0..count)
+            .map(|i| BlogPost {
+                slug: format!("post-{}", i),
+                ..BlogPost::default()
+            })
+            .collect()
+    }
+
+    fn slugs(paged: &PagedPosts) -> Vec<&str> {
+        paged.posts.iter().map(|post| post.slug.as_str()).collect()
+    }
+
+    #[test]
+    fn pages_are_clamped_to_the_available_range() {
+        let first = paginate(&numbered_posts(25), 0, None);
+        assert_eq!(first.page, 1);
+        assert_eq!(first.total_pages, 3);
+        assert_eq!(first.posts.len(), POSTS_PER_PAGE);
+        assert_eq!(first.next_cursor.as_deref(), Some("post-9"));
+
+        let last = paginate(&numbered_posts(25), 99, None);
+        assert_eq!(last.page, 3);
+        assert_eq!(
+            slugs(&last),
+            ["post-20", "post-21", "post-22", "post-23", "post-24"]
+        );
+        assert_eq!(last.next_cursor, None);
+
+        let empty = paginate(&[], 2, None);
+        assert_eq!(
+            (empty.page, empty.total_pages, empty.total_posts),
+            (1, 1, 0)
+        );
+        assert_eq!(empty.next_cursor, None);
+    }
+
+    #[test]
+    fn cursor_continues_after_its_post() {
+        let paged = paginate(&numbered_posts(25), 1, Some("post-14"));
+        assert_eq!(
+            paged.posts.first().map(|post| post.slug.as_str()),
+            Some("post-15")
+        );
+        assert_eq!(paged.posts.len(), POSTS_PER_PAGE);
+        // post-24 is the last post, so this is the end
+        assert_eq!(paged.next_cursor, None);
+
+        // A cursor on the last post leaves nothing more to load
+        let end = paginate(&numbered_posts(25), 1, Some("post-24"));
+        assert_eq!(end.posts.len(), 0);
+        assert_eq!(end.next_cursor, None);
+
+        // An unknown cursor, e.g. a deleted post, falls back to the page number
+        let fallback = paginate(&numbered_posts(25), 2, Some("gone"));
+        assert_eq!(fallback.page, 2);
+        assert_eq!(
+            fallback.posts.first().map(|post| post.slug.as_str()),
+            Some("post-10")
+        );
+    }
+
+    #[test]
+    fn archive_months_follow_the_blog_timezone() {
+        let post = |slug: &str, date: &str| BlogPost {
+            slug: slug.to_string(),
+            date: DateTime::parse_from_rfc3339(date).unwrap().to_utc(),
+            ..BlogPost::default()
+        };
+        // 23:30 UTC on New Year's Eve is already January in Vienna
+        let posts = vec![
+            post("new-year", "2023-12-31T23:30:00Z"),
+            post("december", "2023-12-15T12:00:00Z"),
+        ];
+
+        let years = group_archive_in(posts.clone(), chrono_tz::Europe::Vienna);
+        // (year, month, slug, day) of every post
+        let grouped: Vec<(i32, u32, &str, u32)> = years
+            .iter()
+            .flat_map(|year| {
+                year.months.iter().flat_map(move |month| {
+                    month
+                        .posts
+                        .iter()
+                        .map(move |p| (year.year, month.month, p.slug.as_str(), p.day))
+                })
+            })
+            .collect();
+        assert_eq!(
+            grouped,
+            [(2024, 1, "new-year", 1), (2023, 12, "december", 15)]
+        );
+        assert_eq!(years.len(), 2);
+
+        let utc = group_archive_in(posts, chrono_tz::UTC);
+        assert_eq!(utc.len(), 1);
+        assert_eq!(utc[0].count(), 2);
     }
 }
@@ -0,0 +1,371 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VideoSource {
+    File { url: String, mime: String },
+    YouTube { id: String },
+    Vimeo { id: String },
+}
+
+impl VideoSource {
+    // Video files and YouTube/Vimeo links written with image syntax
+    pub fn from_url(url: &str, resolved_url: &str) -> Option<Self> {
+        if let Some(source) = embed_source(url) {
+            return Some(source);
+        }
+
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let extension = std::path::Path::new(path)
+            .extension()?
+            .to_str()?
+            .to_lowercase();
+        let mime = match extension.as_str() {
+            "mp4" => "video/mp4",
+            "webm" => "video/webm",
+            "mov" => "video/quicktime",
+            _ => return None,
+        };
+        Some(VideoSource::File {
+            url: resolved_url.to_string(),
+            mime: mime.to_string(),
+        })
+    }
+
+    fn provider(&self) -> &'static str {
+        match self {
+            VideoSource::File { .. } => "Video",
+            VideoSource::YouTube { .. } => "YouTube",
+            VideoSource::Vimeo { .. } => "Vimeo",
+        }
+    }
+
+    // Player URL, only requested once the reader asks for it
+    fn embed_url(&self) -> Option<String> {
+        match self {
+            VideoSource::File { .. } => None,
+            VideoSource::YouTube { id } => Some(format!(
+                "https://www.youtube-nocookie.com/embed/{}?autoplay=1",
+                id
+            )),
+            VideoSource::Vimeo { id } => Some(format!(
+                "https://player.vimeo.com/video/{}?autoplay=1&dnt=1",
+                id
+            )),
+        }
+    }
+
+    fn watch_url(&self) -> String {
+        match self {
+            VideoSource::File { url, .. } => url.clone(),
+            VideoSource::YouTube { id } => format!("https://www.youtube.com/watch?v={}", id),
+            VideoSource::Vimeo { id } => format!("https://vimeo.com/{}", id),
+        }
+    }
+}
+
+fn is_video_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn embed_source(url: &str) -> Option<VideoSource> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let host = host.trim_start_matches("www.").trim_start_matches("m.");
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let path = path.split('#').next().unwrap_or(path);
+
+    let id = match host {
+        "youtu.be" => path.to_string(),
+        "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => match path {
+            "watch" => query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("v="))?
+                .to_string(),
+            _ => ["embed/", "shorts/", "live/"]
+                .iter()
+                .find_map(|prefix| path.strip_prefix(prefix))?
+                .to_string(),
+        },
+        "vimeo.com" | "player.vimeo.com" => {
+            let id = path.strip_prefix("video/").unwrap_or(path);
+            let id = id.split('/').next().unwrap_or(id);
+            return (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+                .then(|| VideoSource::Vimeo { id: id.to_string() });
+        }
+        _ => return None,
+    };
+    is_video_id(&id).then_some(VideoSource::YouTube { id })
+}
+
+// A WebVTT file found next to a video, e.g. `talk.en.vtt` for `talk.mp4`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoTrack {
+    pub src: String,
+    pub srclang: Option<String>,
+    pub label: String,
+}
+
+// Poster frame and subtitles found next to a local video
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct VideoAssets {
+    pub poster: Option<String>,
+    pub tracks: Vec<VideoTrack>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Video {
+    pub source: VideoSource,
+    // Alt text, used as the accessible name
+    pub title: String,
+    // What's left of the image title once options are taken out
+    pub caption: Option<String>,
+    pub autoplay: bool,
+    pub muted: bool,
+    pub looped: bool,
+    pub assets: VideoAssets,
+}
+
+impl Video {
+    // `autoplay`, `muted` and `loop` in the image title are options, the rest is a caption.
+    // Browsers only autoplay muted videos, so `autoplay` implies `muted`.
+    pub fn new(source: VideoSource, alt: &str, title: &str) -> Self {
+        let mut video = Video {
+            source,
+            title: alt.trim().to_string(),
+            caption: None,
+            autoplay: false,
+            muted: false,
+            looped: false,
+            assets: VideoAssets::default(),
+        };
+
+        let mut caption = Vec::new();
+        for word in title.split_whitespace() {
+            match word.to_lowercase().as_str() {
+                "autoplay" => {
+                    video.autoplay = true;
+                    video.muted = true;
+                }
+                "muted" => video.muted = true,
+                "loop" => video.looped = true,
+                _ => caption.push(word),
+            }
+        }
+        if !caption.is_empty() {
+            video.caption = Some(caption.join(" "));
+        }
+        video
+    }
+}
+
+fn caption_html(caption: Option<&str>) -> String {
+    caption.map_or_else(String::new, |caption| {
+        format!(
+            r#"<figcaption class="mt-3 text-sm text-muted-foreground text-center">{}</figcaption>"#,
+            html_escape::encode_text(caption)
+        )
+    })
+}
+
+/// Markup for a video file, or a plain link for embeds (used in feeds).
+pub fn video_html(video: &Video) -> String {
+    let VideoSource::File { url, mime } = &video.source else {
+        return format!(
+            r#"<p><a href="{}">{} on {}</a></p>"#,
+            html_escape::encode_double_quoted_attribute(&video.source.watch_url()),
+            html_escape::encode_text(if video.title.is_empty() {
+                "Watch the video"
+            } else {
+                &video.title
+            }),
+            video.source.provider()
+        );
+    };
+
+    let mut attributes = String::new();
+    // A looping autoplay clip behaves like an animated image, so it gets no controls
+    if !(video.autoplay && video.looped) {
+        attributes.push_str(" controls");
+    }
+    for (enabled, attribute) in [
+        (video.autoplay, " autoplay playsinline"),
+        (video.muted, " muted"),
+        (video.looped, " loop"),
+    ] {
+        if enabled {
+            attributes.push_str(attribute);
+        }
+    }
+    if let Some(poster) = &video.assets.poster {
+        attributes.push_str(&format!(
+            r#" poster="{}""#,
+            html_escape::encode_double_quoted_attribute(poster)
+        ));
+    }
+    if !video.title.is_empty() {
+        attributes.push_str(&format!(
+            r#" aria-label="{}""#,
+            html_escape::encode_double_quoted_attribute(&video.title)
+        ));
+    }
+
+    let mut tracks = String::new();
+    for track in &video.assets.tracks {
+        let srclang = track.srclang.as_ref().map_or_else(String::new, |lang| {
+            format!(
+                r#" srclang="{}""#,
+                html_escape::encode_double_quoted_attribute(lang)
+            )
+        });
+        tracks.push_str(&format!(
+            r#"<track kind="subtitles" src="{}"{} label="{}">"#,
+            html_escape::encode_double_quoted_attribute(&track.src),
+            srclang,
+            html_escape::encode_double_quoted_attribute(&track.label)
+        ));
+    }
+
+    format!(
+        r#"<figure class="my-8"><video class="w-full border-2 border-border" preload="metadata"{}><source src="{}" type="{}">{}Your browser does not support the video tag.</video>{}</figure>"#,
+        attributes,
+        html_escape::encode_double_quoted_attribute(url),
+        mime,
+        tracks,
+        caption_html(video.caption.as_deref())
+    )
+}
+
+#[component]
+pub fn VideoPlayer(video: Video) -> impl IntoView {
+    match video.source.embed_url() {
+        Some(embed_url) => view! { <VideoEmbed video=video embed_url=embed_url/> }.into_view(),
+        None => view! { <div inner_html=video_html(&video)></div> }.into_view(),
+    }
+}
+
+// Click-to-load facade: nothing is requested from the provider until the reader hits play
+#[component]
+fn VideoEmbed(video: Video, embed_url: String) -> impl IntoView {
+    let (loaded, set_loaded) = create_signal(false);
+    let provider = video.source.provider();
+    let title = if video.title.is_empty() {
+        format!("{} video", provider)
+    } else {
+        video.title.clone()
+    };
+    let watch_url = video.source.watch_url();
+
+    view! {
+        <figure class="my-8">
+            <div class="relative w-full aspect-video border-2 border-border bg-muted">
+                <Show
+                    when=move || loaded.get()
+                    fallback={
+                        let title = title.clone();
+                        move || view! {
+                            <button
+                                class="absolute inset-0 flex flex-col items-center justify-center gap-3 p-4 text-center text-foreground hover:bg-accent transition-colors cursor-pointer"
+                                aria-label=format!("Play {}", title)
+                                on:click=move |_| set_loaded.set(true)
+                            >
+                                <svg class="w-14 h-14" fill="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                                    <path d="M8 5v14l11-7z"></path>
+                                </svg>
+                                <span class="font-semibold">{title.clone()}</span>
+                                <span class="text-xs text-muted-foreground">
+                                    "Loads the " {provider} " player, which may set cookies"
+                                </span>
+                            </button>
+                        }
+                    }
+                >
+                    <iframe
+                        class="absolute inset-0 w-full h-full"
+                        src=embed_url.clone()
+                        title=title.clone()
+                        allow="autoplay; encrypted-media; fullscreen; picture-in-picture"
+                        allowfullscreen=true
+                    ></iframe>
+                </Show>
+            </div>
+            <figcaption class="mt-3 text-sm text-muted-foreground text-center">
+                {video.caption.clone().map(|caption| view! { <span>{caption}" · "</span> })}
+                <a href=watch_url target="_blank" rel="noopener noreferrer">"Watch on " {provider}</a>
+            </figcaption>
+        </figure>
+    }
+}
+
+#[cfg(feature = "ssr")]
+mod assets {
+    use super::{VideoAssets, VideoSource, VideoTrack};
+    use crate::components::post::images::{asset_path, url_segment};
+    use crate::components::post::markdown::resolve_asset_url;
+    use pulldown_cmark::{Event, Parser, Tag};
+    use std::collections::HashMap;
+
+    const POSTER_EXTENSIONS: [&str; 4] = ["jpg", "jpeg", "png", "webp"];
+
+    /// Posters and subtitle tracks next to each local video in a post, keyed by resolved URL.
+    pub fn video_assets(content: &str) -> HashMap<String, VideoAssets> {
+        Parser::new(content)
+            .filter_map(|event| match event {
+                Event::Start(Tag::Image(_, url, _)) => {
+                    match VideoSource::from_url(&url, &resolve_asset_url(&url, None))? {
+                        VideoSource::File { url, .. } => Some(url),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .filter_map(|url| Some((url.clone(), sibling_assets(&url)?)))
+            .collect()
+    }
+
+    fn sibling_assets(url: &str) -> Option<VideoAssets> {
+        let path = asset_path(url)?;
+        let stem = path.file_stem()?.to_str()?;
+        let dir = path.parent()?;
+        // URL of a file in the same directory as the video
+        let url_dir = &url[..=url.rfind('/')?];
+
+        let poster = POSTER_EXTENSIONS.iter().find_map(|extension| {
+            let name = format!("{}.{}", stem, extension);
+            dir.join(&name)
+                .is_file()
+                .then(|| format!("{}{}", url_dir, url_segment(&name)))
+        });
+
+        // `talk.vtt`, or `talk.en.vtt`, `talk.de.vtt`, ... one per language
+        let mut tracks: Vec<VideoTrack> = std::fs::read_dir(dir)
+            .ok()?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                let middle = name.strip_prefix(stem)?.strip_suffix(".vtt")?;
+                let srclang = match middle {
+                    "" => None,
+                    lang => Some(lang.strip_prefix('.')?.to_string()),
+                };
+                Some(VideoTrack {
+                    src: format!("{}{}", url_dir, url_segment(&name)),
+                    label: srclang
+                        .as_deref()
+                        .map_or_else(|| "Subtitles".to_string(), str::to_uppercase),
+                    srclang,
+                })
+            })
+            .collect();
+        tracks.sort_by(|a, b| a.srclang.cmp(&b.srclang));
+
+        (poster.is_some() || !tracks.is_empty()).then_some(VideoAssets { poster, tracks })
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub use assets::video_assets;
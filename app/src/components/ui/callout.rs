@@ -0,0 +1,90 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+// The five GitHub alert types, written as `> [!NOTE]` etc. in Markdown
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CalloutKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl CalloutKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "NOTE" => Some(CalloutKind::Note),
+            "TIP" => Some(CalloutKind::Tip),
+            "IMPORTANT" => Some(CalloutKind::Important),
+            "WARNING" => Some(CalloutKind::Warning),
+            "CAUTION" => Some(CalloutKind::Caution),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CalloutKind::Note => "Note",
+            CalloutKind::Tip => "Tip",
+            CalloutKind::Important => "Important",
+            CalloutKind::Warning => "Warning",
+            CalloutKind::Caution => "Caution",
+        }
+    }
+
+    pub fn classes(&self) -> &'static str {
+        match self {
+            CalloutKind::Note => "border-blue-500 bg-blue-50 dark:bg-blue-900/20",
+            CalloutKind::Tip => "border-green-500 bg-green-50 dark:bg-green-900/20",
+            CalloutKind::Important => "border-purple-500 bg-purple-50 dark:bg-purple-900/20",
+            CalloutKind::Warning => "border-yellow-500 bg-yellow-50 dark:bg-yellow-900/20",
+            CalloutKind::Caution => "border-red-500 bg-red-50 dark:bg-red-900/20",
+        }
+    }
+
+    pub fn title_classes(&self) -> &'static str {
+        match self {
+            CalloutKind::Note => "text-blue-700 dark:text-blue-400",
+            CalloutKind::Tip => "text-green-700 dark:text-green-400",
+            CalloutKind::Important => "text-purple-700 dark:text-purple-400",
+            CalloutKind::Warning => "text-yellow-700 dark:text-yellow-400",
+            CalloutKind::Caution => "text-red-700 dark:text-red-400",
+        }
+    }
+
+    fn icon_path(&self) -> &'static str {
+        match self {
+            CalloutKind::Note => "M13 16h-1v-4h-1m1-4h.01M21 12a9 9 0 11-18 0 9 9 0 0118 0z",
+            CalloutKind::Tip => "M9.663 17h4.673M12 3v1m6.364 1.636l-.707.707M21 12h-1M4 12H3m3.343-5.657l-.707-.707m2.828 9.9a5 5 0 117.072 0l-.548.547A3.374 3.374 0 0014 18.469V19a2 2 0 11-4 0v-.531c0-.895-.356-1.754-.988-2.386l-.548-.547z",
+            CalloutKind::Important => "M12 8v4m0 4h.01M21 12a9 9 0 11-18 0 9 9 0 0118 0z",
+            CalloutKind::Warning => "M12 9v2m0 4h.01m-6.938 4h13.856c1.54 0 2.502-1.667 1.732-3L13.732 4c-.77-1.333-2.694-1.333-3.464 0L3.34 16c-.77 1.333.192 3 1.732 3z",
+            CalloutKind::Caution => "M18.364 18.364A9 9 0 005.636 5.636m12.728 12.728A9 9 0 015.636 5.636m12.728 12.728L5.636 5.636",
+        }
+    }
+}
+
+#[component]
+pub fn Callout(
+    kind: CalloutKind,
+    // Defaults to the kind's label, e.g. "Warning"
+    #[prop(optional)] title: Option<String>,
+    children: Children,
+) -> impl IntoView {
+    let title = title.unwrap_or_else(|| kind.label().to_string());
+
+    view! {
+        <aside
+            class=format!("callout border-l-4 px-4 py-3 my-6 sm:my-7 md:my-8 {}", kind.classes())
+            role="note"
+        >
+            <div class=format!("flex items-center gap-2 font-semibold mb-2 {}", kind.title_classes())>
+                <svg class="w-5 h-5 flex-shrink-0" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d=kind.icon_path()></path>
+                </svg>
+                {title}
+            </div>
+            {children()}
+        </aside>
+    }
+}
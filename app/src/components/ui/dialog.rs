@@ -10,6 +10,7 @@ pub fn Dialog(
     show_settings: RwSignal<bool>,
     animation_speed: RwSignal<u64>,
     population_density: RwSignal<f64>,
+    infinite_scroll: RwSignal<bool>,
     theme_mode: RwSignal<String>,
     on_theme_change: Callback<String>,
 ) -> impl IntoView {
@@ -128,6 +129,41 @@ pub fn Dialog(
                             </div>
                         </div>
 
+                        // Home Page Listing Control
+                        <div>
+                            <label class="block text-xs sm:text-sm font-medium text-popover-foreground mb-1.5 sm:mb-2">
+                                Post List
+                            </label>
+                            <div class="grid grid-cols-2 gap-1">
+                                <button
+                                    class=move || format!(
+                                        "px-2 sm:px-3 py-1.5 sm:py-2 text-[10px] sm:text-xs font-medium border transition-colors cursor-pointer {}",
+                                        if infinite_scroll.get() {
+                                            "bg-transparent text-foreground border-border hover:bg-muted"
+                                        } else {
+                                            "bg-foreground text-background border-foreground"
+                                        }
+                                    )
+                                    on:click=move |_| infinite_scroll.set(false)
+                                >
+                                    Pages
+                                </button>
+                                <button
+                                    class=move || format!(
+                                        "px-2 sm:px-3 py-1.5 sm:py-2 text-[10px] sm:text-xs font-medium border transition-colors cursor-pointer {}",
+                                        if infinite_scroll.get() {
+                                            "bg-foreground text-background border-foreground"
+                                        } else {
+                                            "bg-transparent text-foreground border-border hover:bg-muted"
+                                        }
+                                    )
+                                    on:click=move |_| infinite_scroll.set(true)
+                                >
+                                    Infinite Scroll
+                                </button>
+                            </div>
+                        </div>
+
                         // Theme Mode Control
                         <div>
                             <label class="block text-xs sm:text-sm font-medium text-popover-foreground mb-1.5 sm:mb-2">
@@ -1,5 +1,6 @@
 pub mod badge;
 pub mod button;
+pub mod callout;
 pub mod card;
 pub mod dialog;
 pub mod life;
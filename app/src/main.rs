@@ -4,13 +4,58 @@ async fn main() -> std::io::Result<()> {
     use actix_files::Files;
     use actix_web::*;
     use blog::app::*;
+    use blog::components::post::images::{image_cache_dir, ASSETS_DIR};
+    use blog::components::post::index::{post_index, watch_posts, PostIndex};
+    use blog::components::post::{set_metrics_store, store::metrics_store_from_env, POSTS_DIR};
+    use blog::routes::{feed, lint, sitemap};
     use leptos::*;
     use leptos_actix::{generate_route_list, LeptosRoutes};
 
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    // `blog --check` lints every post and exits instead of serving
+    if std::env::args().any(|arg| arg == "--check") {
+        let dir = std::path::Path::new(POSTS_DIR);
+        if !dir.is_dir() {
+            eprintln!("posts directory {} not found", dir.display());
+            std::process::exit(1);
+        }
+
+        let index = PostIndex::load(dir);
+        let report = index.lint_report();
+        if !index.errors().is_empty() {
+            eprint!("{}", report);
+            std::process::exit(1);
+        }
+        print!("{}", report);
+        return Ok(());
+    }
+
     let conf = get_configuration(None).await.unwrap();
     let addr = conf.leptos_options.site_addr;
     let routes = generate_route_list(App);
 
+    let metrics_store = metrics_store_from_env().expect("failed to open metrics store");
+    set_metrics_store(metrics_store);
+
+    // Build the post index up front and keep it fresh while the server runs. Files that
+    // fail to parse are logged by the index, and listed at /lint while previewing drafts
+    let index = post_index();
+    println!("indexed {} posts", index.read().unwrap().len());
+    let _watcher = watch_posts(index, POSTS_DIR, ASSETS_DIR)
+        .map_err(|e| tracing::warn!("post watcher disabled: {}", e))
+        .ok();
+
+    // Must exist before it is mounted, or resized images would 404 until a restart
+    if let Err(e) = std::fs::create_dir_all(image_cache_dir()) {
+        tracing::warn!("image cache disabled: {}", e);
+    }
+
     println!("listening on http://{}", &addr);
 
     HttpServer::new(move || {
@@ -21,7 +66,13 @@ async fn main() -> std::io::Result<()> {
         App::new()
             .leptos_routes(leptos_options.to_owned(), routes.to_owned(), App)
             .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
-            .service(Files::new("/assets/", "posts/").show_files_listing())
+            .service(feed::rss_feed)
+            .service(feed::atom_feed)
+            .service(sitemap::sitemap)
+            .service(sitemap::robots)
+            .service(lint::lint_report)
+            .service(Files::new("/assets/", ASSETS_DIR).show_files_listing())
+            .service(Files::new("/images/", image_cache_dir()))
             .service(Files::new("/", site_root))
             .wrap(middleware::DefaultHeaders::new().add(("Cache-Control", "no-cache")))
             .wrap(middleware::Compress::default())
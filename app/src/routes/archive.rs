@@ -0,0 +1,184 @@
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+
+#[cfg(feature = "ssr")]
+use crate::components::post::{get_all_posts, group_archive};
+use crate::components::post::{ArchiveMonth, ArchiveYear};
+use crate::routes::set_not_found_status;
+
+#[server(GetArchive, "/api")]
+pub async fn get_archive(
+    year: Option<i32>,
+    month: Option<u32>,
+) -> Result<Vec<ArchiveYear>, ServerFnError> {
+    let posts = get_all_posts()
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    Ok(group_archive(posts)
+        .into_iter()
+        .filter(|archive| year.map_or(true, |year| archive.year == year))
+        .map(|mut archive| {
+            archive
+                .months
+                .retain(|archive_month| month.map_or(true, |month| archive_month.month == month));
+            archive
+        })
+        .filter(|archive| !archive.months.is_empty())
+        .collect())
+}
+
+// Year and month from the URL; `None` when either is present but not a valid number
+fn archive_filter(params: &ParamsMap) -> Option<(Option<i32>, Option<u32>)> {
+    let year = match params.get("year") {
+        Some(year) => Some(year.parse().ok()?),
+        None => None,
+    };
+    let month = match params.get("month") {
+        Some(month) => Some(
+            month
+                .parse()
+                .ok()
+                .filter(|month| (1..=12).contains(month))?,
+        ),
+        None => None,
+    };
+    Some((year, month))
+}
+
+fn month_name(month: u32) -> &'static str {
+    u8::try_from(month)
+        .ok()
+        .and_then(|month| chrono::Month::try_from(month).ok())
+        .map_or("", |month| month.name())
+}
+
+fn short_month_name(month: u32) -> &'static str {
+    month_name(month).get(..3).unwrap_or("")
+}
+
+fn year_href(year: i32) -> String {
+    format!("/archive/{}", year)
+}
+
+fn month_href(year: i32, month: u32) -> String {
+    format!("/archive/{}/{:02}", year, month)
+}
+
+fn count_label(count: usize) -> String {
+    format!("{} {}", count, if count == 1 { "post" } else { "posts" })
+}
+
+#[component]
+pub fn ArchivePage() -> impl IntoView {
+    let params = use_params_map();
+    let filter = move || params.with(archive_filter);
+
+    let archive = create_resource(filter, |filter| async move {
+        match filter {
+            Some((year, month)) => get_archive(year, month).await,
+            None => Ok(Vec::new()),
+        }
+    });
+
+    let heading = move || match filter() {
+        Some((Some(year), Some(month))) => format!("{} {}", month_name(month), year),
+        Some((Some(year), None)) => year.to_string(),
+        _ => "Archive".to_string(),
+    };
+    // One level up from the current view
+    let back = move || match filter() {
+        Some((Some(year), Some(_))) => (year_href(year), format!("← {}", year)),
+        Some((Some(_), None)) => ("/archive".to_string(), "← Archive".to_string()),
+        _ => ("/".to_string(), "← Back to posts".to_string()),
+    };
+
+    view! {
+        <div class="max-w-4xl mx-auto px-4 sm:px-6 pt-3 pb-12 sm:pb-16 min-h-screen">
+            {move || if filter().is_some() {
+                view! {
+                    <Title text=move || format!("{} - blog", heading())/>
+
+                    <A href=move || back().0 class="text-sm sm:text-base md:text-lg text-primary hover:text-primary/80 mb-6 sm:mb-7 md:mb-8 inline-block">
+                        {move || back().1}
+                    </A>
+
+                    <h1 class="text-2xl sm:text-3xl md:text-4xl font-bold mb-6 sm:mb-8 text-foreground">{heading}</h1>
+
+                    <Suspense fallback=move || view! { <div></div> }>
+                        {move || {
+                            archive.get()
+                                .map(|archive| match archive {
+                                    Ok(years) if years.is_empty() => view! {
+                                        <p class="text-muted-foreground text-lg">"No posts from this time"</p>
+                                    }.into_view(),
+                                    Ok(years) => years.into_iter().map(year_view).collect_view(),
+                                    Err(e) => view! {
+                                        <p class="text-destructive">"Error loading archive: " {e.to_string()}</p>
+                                    }.into_view(),
+                                })
+                        }}
+                    </Suspense>
+                }.into_view()
+            } else {
+                // Not a year or month, e.g. `/archive/abc`
+                set_not_found_status();
+                view! {
+                    <Title text="Page not found - blog"/>
+                    <div class="text-center py-12">
+                        <h1 class="text-3xl font-bold text-foreground mb-4">
+                            "Page not found"
+                        </h1>
+                        <A href="/archive" class="text-primary hover:text-primary/80">
+                            "Back to the archive"
+                        </A>
+                    </div>
+                }.into_view()
+            }}
+        </div>
+    }
+}
+
+fn year_view(archive: ArchiveYear) -> impl IntoView {
+    let year = archive.year;
+    let count = archive.count();
+
+    view! {
+        <section class="mb-8 sm:mb-10">
+            <h2 class="flex items-baseline gap-3 text-xl sm:text-2xl font-bold mb-4 text-foreground">
+                <A href=year_href(year) class="hover:text-primary">{year}</A>
+                <span class="text-sm font-normal text-muted-foreground">{count_label(count)}</span>
+            </h2>
+            {archive.months.into_iter().map(|month| month_view(year, month)).collect_view()}
+        </section>
+    }
+}
+
+fn month_view(year: i32, archive: ArchiveMonth) -> impl IntoView {
+    let month = archive.month;
+    let count = archive.posts.len();
+
+    view! {
+        <div class="mb-6">
+            <h3 class="flex items-baseline gap-3 text-base sm:text-lg font-semibold mb-2 text-foreground">
+                <A href=month_href(year, month) class="hover:text-primary">{month_name(month)}</A>
+                <span class="text-sm font-normal text-muted-foreground">{count_label(count)}</span>
+            </h3>
+            <ul class="space-y-1.5">
+                {archive.posts.into_iter().map(|post| view! {
+                    <li class="flex gap-4 text-sm sm:text-base">
+                        <time
+                            datetime=post.date.to_rfc3339()
+                            class="w-14 flex-shrink-0 text-muted-foreground tabular-nums"
+                        >
+                            {format!("{} {:02}", short_month_name(month), post.day)}
+                        </time>
+                        <A href=format!("/post/{}", post.slug) class="text-foreground hover:text-primary truncate">
+                            {post.title}
+                        </A>
+                    </li>
+                }).collect_view()}
+            </ul>
+        </div>
+    }
+}
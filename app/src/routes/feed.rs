@@ -0,0 +1,277 @@
+use actix_web::{get, HttpResponse};
+use html_escape::{encode_double_quoted_attribute as attr, encode_text as text};
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::components::post::{get_all_posts, markdown::render_post_markdown, BlogPost};
+
+// Number of most recent posts included in each feed
+const FEED_LIMIT: usize = 20;
+
+// Used when BLOG_SITE_URL is unset, so links never depend on a client-supplied Host header
+const DEFAULT_SITE_URL: &str = "http://localhost:3000";
+
+static SITE_URL: LazyLock<String> = LazyLock::new(|| match std::env::var("BLOG_SITE_URL") {
+    Ok(url) if !url.trim().is_empty() => url.trim().trim_end_matches('/').to_string(),
+    _ => {
+        tracing::warn!(
+            "BLOG_SITE_URL is not set, feeds and the sitemap link to {}",
+            DEFAULT_SITE_URL
+        );
+        DEFAULT_SITE_URL.to_string()
+    }
+});
+
+/// Public base URL of the site from `BLOG_SITE_URL`, without a trailing slash.
+pub fn site_url() -> &'static str {
+    &SITE_URL
+}
+
+fn site_title() -> String {
+    std::env::var("BLOG_TITLE").unwrap_or_else(|_| "blog".to_string())
+}
+
+fn site_description() -> String {
+    std::env::var("BLOG_DESCRIPTION")
+        .unwrap_or_else(|_| "A blog built with Leptos and Rust".to_string())
+}
+
+// Full post HTML is opt-in since it makes feeds considerably larger
+fn include_full_content() -> bool {
+    matches!(
+        std::env::var("BLOG_FEED_FULL_CONTENT").as_deref(),
+        Ok("1") | Ok("true")
+    )
+}
+
+async fn feed_posts() -> Result<Vec<BlogPost>, HttpResponse> {
+    get_all_posts()
+        .await
+        .map(|posts| posts.into_iter().take(FEED_LIMIT).collect())
+        .map_err(|e| HttpResponse::InternalServerError().body(e.to_string()))
+}
+
+// Most recent publication or update among the feed's posts
+fn last_updated(posts: &[BlogPost]) -> Option<chrono::DateTime<chrono::Utc>> {
+    posts.iter().map(BlogPost::last_modified).max()
+}
+
+// Rendered Markdown uses root-relative asset links, which feed readers can't resolve
+fn absolute_html(post: &BlogPost, base: &str) -> String {
+    absolute_urls(&render_post_markdown(post), base)
+}
+
+// Each post's HTML when feeds carry full content, otherwise nothing. Rendering can shell out
+// to mermaid-cli for diagrams, so it runs on the blocking pool.
+async fn full_contents(posts: &[BlogPost], base: &str) -> Result<Vec<String>, HttpResponse> {
+    if !include_full_content() {
+        return Ok(Vec::new());
+    }
+    let posts = posts.to_vec();
+    let base = base.to_string();
+    tokio::task::spawn_blocking(move || {
+        posts
+            .iter()
+            .map(|post| absolute_html(post, &base))
+            .collect()
+    })
+    .await
+    .map_err(|e| HttpResponse::InternalServerError().body(e.to_string()))
+}
+
+static URL_ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b(src|href|poster|srcset)="([^"]*)""#).unwrap());
+
+// Prefix root-relative URLs in link and media attributes with `base`. Protocol-relative
+// (`//cdn.example/x.png`) and absolute URLs are left as they are
+fn absolute_urls(html: &str, base: &str) -> String {
+    let absolute = |url: &str| {
+        if url.starts_with('/') && !url.starts_with("//") {
+            format!("{}{}", base, url)
+        } else {
+            url.to_string()
+        }
+    };
+
+    URL_ATTRIBUTE
+        .replace_all(html, |caps: &regex::Captures<'_>| {
+            let value = &caps[2];
+            let value = if &caps[1] == "srcset" {
+                // Comma-separated candidates, each a URL and an optional descriptor
+                value
+                    .split(',')
+                    .map(|candidate| {
+                        let candidate = candidate.trim();
+                        match candidate.split_once(' ') {
+                            Some((url, descriptor)) => format!("{} {}", absolute(url), descriptor),
+                            None => absolute(candidate),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            } else {
+                absolute(value)
+            };
+            format!(r#"{}="{}""#, &caps[1], value)
+        })
+        .into_owned()
+}
+
+#[get("/rss.xml")]
+pub async fn rss_feed() -> HttpResponse {
+    let posts = match feed_posts().await {
+        Ok(posts) => posts,
+        Err(response) => return response,
+    };
+    let base = site_url();
+    let contents = match full_contents(&posts, base).await {
+        Ok(contents) => contents,
+        Err(response) => return response,
+    };
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:content="http://purl.org/rss/1.0/modules/content/"><channel>"#);
+    xml.push_str(&format!("<title>{}</title>", text(&site_title())));
+    xml.push_str(&format!("<link>{}/</link>", text(base)));
+    xml.push_str(&format!(
+        "<description>{}</description>",
+        text(&site_description())
+    ));
+    xml.push_str(&format!(
+        r#"<atom:link href="{}/rss.xml" rel="self" type="application/rss+xml"/>"#,
+        attr(base)
+    ));
+    if let Some(updated) = last_updated(&posts) {
+        xml.push_str(&format!(
+            "<lastBuildDate>{}</lastBuildDate>",
+            updated.to_rfc2822()
+        ));
+    }
+
+    for (index, post) in posts.iter().enumerate() {
+        let link = format!("{}/post/{}", base, post.slug);
+        xml.push_str("<item>");
+        xml.push_str(&format!("<title>{}</title>", text(&post.title)));
+        xml.push_str(&format!("<link>{}</link>", text(&link)));
+        xml.push_str(&format!(
+            r#"<guid isPermaLink="true">{}</guid>"#,
+            text(&link)
+        ));
+        xml.push_str(&format!("<pubDate>{}</pubDate>", post.date.to_rfc2822()));
+        xml.push_str(&format!(
+            "<description>{}</description>",
+            text(&post.excerpt)
+        ));
+        for tag in &post.tags {
+            xml.push_str(&format!("<category>{}</category>", text(tag)));
+        }
+        if let Some(content) = contents.get(index) {
+            xml.push_str(&format!(
+                "<content:encoded>{}</content:encoded>",
+                text(content)
+            ));
+        }
+        xml.push_str("</item>");
+    }
+
+    xml.push_str("</channel></rss>");
+
+    HttpResponse::Ok()
+        .content_type("application/rss+xml; charset=utf-8")
+        .body(xml)
+}
+
+#[get("/atom.xml")]
+pub async fn atom_feed() -> HttpResponse {
+    let posts = match feed_posts().await {
+        Ok(posts) => posts,
+        Err(response) => return response,
+    };
+    let base = site_url();
+    let contents = match full_contents(&posts, base).await {
+        Ok(contents) => contents,
+        Err(response) => return response,
+    };
+    let updated = last_updated(&posts).unwrap_or_else(chrono::Utc::now);
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    xml.push_str(&format!("<title>{}</title>", text(&site_title())));
+    xml.push_str(&format!(
+        "<subtitle>{}</subtitle>",
+        text(&site_description())
+    ));
+    xml.push_str(&format!(r#"<link href="{}/"/>"#, attr(base)));
+    xml.push_str(&format!(
+        r#"<link rel="self" type="application/atom+xml" href="{}/atom.xml"/>"#,
+        attr(base)
+    ));
+    xml.push_str(&format!("<id>{}/</id>", text(base)));
+    xml.push_str(&format!("<updated>{}</updated>", updated.to_rfc3339()));
+
+    for (index, post) in posts.iter().enumerate() {
+        let link = format!("{}/post/{}", base, post.slug);
+        xml.push_str("<entry>");
+        xml.push_str(&format!("<title>{}</title>", text(&post.title)));
+        xml.push_str(&format!(r#"<link href="{}"/>"#, attr(&link)));
+        xml.push_str(&format!("<id>{}</id>", text(&link)));
+        xml.push_str(&format!(
+            "<published>{}</published>",
+            post.date.to_rfc3339()
+        ));
+        xml.push_str(&format!(
+            "<updated>{}</updated>",
+            post.last_modified().to_rfc3339()
+        ));
+        xml.push_str(&format!("<summary>{}</summary>", text(&post.excerpt)));
+        for tag in &post.tags {
+            xml.push_str(&format!(r#"<category term="{}"/>"#, attr(tag)));
+        }
+        if let Some(content) = contents.get(index) {
+            xml.push_str(&format!(
+                r#"<content type="html">{}</content>"#,
+                text(content)
+            ));
+        }
+        xml.push_str("</entry>");
+    }
+
+    xml.push_str("</feed>");
+
+    HttpResponse::Ok()
+        .content_type("application/atom+xml; charset=utf-8")
+        .body(xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_relative_urls_get_the_site_base() {
+        assert_eq!(
+            absolute_urls(
+                r#"<a href="/post/x">x</a><img src="/assets/a.png">"#,
+                "https://site"
+            ),
+            r#"<a href="https://site/post/x">x</a><img src="https://site/assets/a.png">"#
+        );
+    }
+
+    #[test]
+    fn protocol_relative_and_absolute_urls_are_kept() {
+        let html = r##"<img src="//cdn.example/x.png"><a href="https://other/">o</a><a href="#fn-1">1</a>"##;
+        assert_eq!(absolute_urls(html, "https://site"), html);
+    }
+
+    #[test]
+    fn every_srcset_candidate_is_rewritten() {
+        assert_eq!(
+            absolute_urls(
+                r#"<source srcset="/images/a-480.webp 480w, /images/a-960.webp 960w">"#,
+                "https://site"
+            ),
+            r#"<source srcset="https://site/images/a-480.webp 480w, https://site/images/a-960.webp 960w">"#
+        );
+    }
+}
@@ -2,25 +2,82 @@ use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
 
+use crate::components::post::{card::PostCard, PagedPosts, PostSummary, SearchHit};
 #[cfg(feature = "ssr")]
-use crate::components::post::get_all_posts;
-use crate::components::post::{BlogPost, PostCardMetrics};
-use crate::components::ui::button::{Button, ButtonVariant};
-use crate::components::ui::card::{Card, CardContent, CardHeader, CardTitle};
+use crate::components::post::{get_all_posts, paginate, search_all_posts};
 use crate::components::ui::life::Life;
 
 #[server(GetPosts, "/api")]
-pub async fn get_posts() -> Result<Vec<crate::components::post::BlogPost>, ServerFnError> {
+pub async fn get_posts(page: usize, cursor: Option<String>) -> Result<PagedPosts, ServerFnError> {
+    let posts = get_all_posts()
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    Ok(paginate(&posts, page, cursor.as_deref()))
+}
+
+// Titles of every listed post, for the search box suggestions
+#[server(GetPostTitles, "/api")]
+pub async fn get_post_titles() -> Result<Vec<String>, ServerFnError> {
     get_all_posts()
+        .await
+        .map(|posts| posts.into_iter().map(|post| post.title).collect())
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+#[server(SearchPosts, "/api")]
+pub async fn search_posts(query: String) -> Result<Vec<SearchHit>, ServerFnError> {
+    search_all_posts(&query)
         .await
         .map_err(|e| ServerFnError::ServerError(e.to_string()))
 }
 
+// Whether the home page loads more posts while scrolling instead of linking to pages.
+// Wrapped so it doesn't clash with the other `RwSignal<bool>` contexts
+#[derive(Clone, Copy)]
+pub struct InfiniteScroll(pub RwSignal<bool>);
+
+// Pause in typing before the search box query is sent
+const SEARCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(250);
+
+// Start loading the next posts this far (in pixels) before the end of the list
+#[cfg(feature = "hydrate")]
+const LOAD_MORE_MARGIN: f64 = 800.0;
+
+fn page_href(page: usize) -> String {
+    if page <= 1 {
+        "/".to_string()
+    } else {
+        format!("/?page={}", page)
+    }
+}
+
 #[component]
 pub fn HomePage() -> impl IntoView {
-    // Get global contexts
-    let posts = use_context::<Resource<(), Result<Vec<BlogPost>, ServerFnError>>>()
-        .expect("posts context not provided");
+    // Pages come from the URL so they work without JavaScript
+    let query = use_query_map();
+    let page = move || {
+        query.with(|query| {
+            query
+                .get("page")
+                .and_then(|page| page.parse::<usize>().ok())
+                .unwrap_or(1)
+        })
+    };
+    let posts = create_resource(page, |page| async move { get_posts(page, None).await });
+
+    let infinite_scroll = use_context::<InfiniteScroll>()
+        .map_or_else(|| RwSignal::new(false), |InfiniteScroll(enabled)| enabled);
+    // Posts appended by infinite scrolling, and the slug to continue after
+    let more_posts = RwSignal::new(Vec::<PostSummary>::new());
+    let cursor = RwSignal::new(None::<String>);
+    let loading = RwSignal::new(false);
+
+    create_effect(move |_| {
+        if let Some(Ok(paged)) = posts.get() {
+            more_posts.set(Vec::new());
+            cursor.set(paged.next_cursor);
+        }
+    });
 
     // Get animation speed, population density, and search query from global context
     let animation_speed = use_context::<RwSignal<u64>>().unwrap_or_else(|| RwSignal::new(100u64));
@@ -29,22 +86,83 @@ pub fn HomePage() -> impl IntoView {
     let search_query =
         use_context::<RwSignal<String>>().unwrap_or_else(|| RwSignal::new(String::new()));
 
-    // Function to filter posts based on search query
-    let filter_posts = move |posts: Vec<crate::components::post::BlogPost>| {
-        let query = search_query.get().trim().to_lowercase();
+    // Search once typing pauses rather than on every keystroke; clearing the box applies at once
+    let searched_query = RwSignal::new(search_query.get_untracked().trim().to_string());
+    let search_later = store_value(leptos_dom::helpers::debounce(
+        SEARCH_DEBOUNCE,
+        move |query: String| searched_query.set(query),
+    ));
+    create_effect(move |_| {
+        let query = search_query.get().trim().to_string();
         if query.is_empty() {
-            posts
-        } else {
-            posts
-                .into_iter()
-                .filter(|post| {
-                    post.title.to_lowercase().contains(&query)
-                        || post.excerpt.to_lowercase().contains(&query)
-                })
-                .collect()
+            searched_query.set(String::new());
+        }
+        // Also replaces a pending search, so it can't land after the box was cleared
+        search_later.update_value(|search| search(query));
+    });
+
+    // Ranked full-text results for the searched query; `None` when not searching
+    let search_results = create_resource(
+        move || searched_query.get(),
+        |query| async move {
+            if query.is_empty() {
+                Ok(None)
+            } else {
+                search_posts(query).await.map(Some)
+            }
+        },
+    );
+
+    let load_more = move || {
+        let Some(after) = cursor.get_untracked() else {
+            return;
+        };
+        if loading.get_untracked() {
+            return;
         }
+        loading.set(true);
+        spawn_local(async move {
+            match get_posts(1, Some(after)).await {
+                Ok(paged) => {
+                    more_posts.update(|posts| posts.extend(paged.posts));
+                    cursor.set(paged.next_cursor);
+                }
+                Err(e) => {
+                    leptos::logging::error!("Error loading more posts: {}", e);
+                    // Fall back to the page links rather than retrying on every scroll
+                    cursor.set(None);
+                }
+            }
+            loading.set(false);
+        });
     };
 
+    #[cfg(feature = "hydrate")]
+    {
+        let handle = window_event_listener(ev::scroll, move |_| {
+            if !infinite_scroll.get_untracked() || !search_query.get_untracked().trim().is_empty() {
+                return;
+            }
+            let window = window();
+            let viewport = window
+                .inner_height()
+                .ok()
+                .and_then(|height| height.as_f64())
+                .unwrap_or(0.0);
+            let scrolled = window.scroll_y().unwrap_or(0.0) + viewport;
+            let height = document()
+                .document_element()
+                .map_or(0.0, |element| f64::from(element.scroll_height()));
+            if scrolled >= height - LOAD_MORE_MARGIN {
+                load_more();
+            }
+        });
+        on_cleanup(move || handle.remove());
+    }
+
+    // Infinite scrolling takes over from the page links while there is more to load
+    let scrolling = move || infinite_scroll.get() && cursor.get().is_some();
+
     view! {
         <>
             <Title text="blog"/>
@@ -53,137 +171,92 @@ pub fn HomePage() -> impl IntoView {
             <div class="relative min-h-screen">
                 <div class="relative z-10 max-w-5xl mx-auto px-2 sm:px-4 md:px-6 lg:px-8 pt-1 sm:pt-6 md:pt-8 pb-6 sm:pb-12 md:pb-16">
                 <main>
-                    <Suspense fallback=move || view! { <div></div> }>
+                    <Transition fallback=move || view! { <div></div> }>
                         {move || {
-                            posts.get()
-                                .map(|posts| match posts {
-                                        Ok(posts) => {
-                                            let filtered_posts = filter_posts(posts);
-                                            if filtered_posts.is_empty() {
-                                                let query = search_query.get();
-                                                if query.trim().is_empty() {
-                                                    view! {
-                                                        <div class="text-center py-16">
-                                                            <p class="text-muted-foreground text-lg">"No posts available"</p>
-                                                        </div>
-                                                    }.into_view()
-                                                } else {
-                                                    // Don't show any message when search has no results
-                                                    view! { <div class="py-16"></div> }.into_view()
-                                                }
+                            match search_results.get() {
+                                Some(Ok(Some(hits))) => {
+                                    // Don't show any message when search has no results
+                                    if hits.is_empty() {
+                                        view! { <div class="py-16"></div> }.into_view()
+                                    } else {
+                                        view! {
+                                            <div class="space-y-2 sm:space-y-3 md:space-y-4">
+                                                {hits.into_iter()
+                                                    .map(|hit| view! { <PostCard post=hit.post snippet=hit.snippet/> })
+                                                    .collect_view()}
+                                            </div>
+                                        }.into_view()
+                                    }
+                                }
+                                Some(Err(e)) => view! {
+                                    <p class="text-destructive text-center text-lg">"Error searching posts: " {e.to_string()}</p>
+                                }.into_view(),
+                                _ => posts.get()
+                                    .map(|posts| match posts {
+                                        Ok(paged) => {
+                                            if paged.posts.is_empty() {
+                                                view! {
+                                                    <div class="text-center py-16">
+                                                        <p class="text-muted-foreground text-lg">"No posts available"</p>
+                                                    </div>
+                                                }.into_view()
                                             } else {
+                                                let PagedPosts { posts, page, total_pages, .. } = paged;
                                                 view! {
                                                     <div class="space-y-2 sm:space-y-3 md:space-y-4">
-                                                        {filtered_posts.into_iter()
-                                                            .map(|post| {
-                                                                let post_url = format!("/post/{}", post.slug);
-                                                                let title = post.title.clone();
-                                                                let excerpt = post.excerpt.clone();
-                                                                let slug_for_share = post.slug.clone();
-
-                                                                view! {
-                                                                    <div class="relative w-full group">
-                                                                        <A
-                                                                            href=post_url
-                                                                            class="block w-full"
-                                                                        >
-                                                                            <Card class="w-full h-20 sm:h-24 md:h-28 flex flex-col hover:bg-accent hover:border-ring focus-within:border-ring border-2 border-border transition-colors cursor-pointer">
-                                                                                <CardHeader class="flex-shrink-0 !p-2 sm:!p-3 md:!p-4 !pb-1 sm:!pb-1 md:!pb-2">
-                                                                                    <CardTitle class="text-card-foreground truncate !mb-0 sm:!mb-1 !text-sm sm:!text-base md:!text-xl font-bold">
-                                                                                        {title.clone()}
-                                                                                    </CardTitle>
-                                                                                </CardHeader>
-                                                                                <CardContent class="flex-1 flex flex-col !pt-0 !px-2 sm:!px-3 md:!px-4 !pb-2 sm:!pb-3 md:!pb-4">
-                                                                                    <p class="text-muted-foreground truncate text-xs sm:text-sm md:text-base">
-                                                                                        {excerpt.clone()}
-                                                                                    </p>
-                                                                                    <div class="flex-1"></div>
-                                                                                </CardContent>
-                                                                            </Card>
-                                                                        </A>
-                                                                        <div class="absolute bottom-1 left-2 sm:bottom-1 sm:left-3 md:bottom-1 md:left-4 pointer-events-none">
-                                                                            <PostCardMetrics
-                                                                                views=post.metrics.views
-                                                                            />
-                                                                        </div>
-                                                                        <div class="absolute bottom-1 right-2 sm:bottom-1 sm:right-3 md:bottom-1 md:right-4 opacity-0 group-hover:opacity-100 transition-opacity">
-                                                                            {
-                                                                                let (is_shared, set_is_shared) = create_signal(false);
-                                                                                view! {
-                                                                                    <Button
-                                                                                        variant=ButtonVariant::Plain
-                                                                                        onclick=Box::new({
-                                                                                            let slug_for_async = slug_for_share.clone();
-                                                                                            let _set_is_shared = set_is_shared;
-                                                                                            move || {
-                                                                                                if is_shared.get() {
-                                                                                                    return;
-                                                                                                }
-
-                                                                                                // Set to shared immediately for visual feedback
-                                                                                                _set_is_shared.set(true);
-
-                                                                                                let _slug_clone = slug_for_async.clone();
-                                                                                                let _set_is_shared_clone = _set_is_shared;
-
-                                                                                                spawn_local(async move {
-                                                                                                    #[cfg(feature = "hydrate")]
-                                                                                                    {
-                                                                                                        if let Some(window) = web_sys::window() {
-                                                                                                            let full_url = format!("{}/post/{}", window.location().origin().unwrap(), _slug_clone);
-
-                                                                                                            let navigator = window.navigator();
-                                                                                                            let clipboard = navigator.clipboard();
-                                                                                                            let promise = clipboard.write_text(&full_url);
-                                                                                                            let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
-                                                                                                        }
-                                                                                                    }
-
-                                                                                                    // Wait 2 seconds then reset
-                                                                                                    #[cfg(feature = "hydrate")]
-                                                                                                    {
-                                                                                                        gloo_timers::future::TimeoutFuture::new(2000).await;
-                                                                                                    }
-                                                                                                    _set_is_shared_clone.set(false);
-                                                                                                });
-                                                                                            }
-                                                                                        })
-                                                                                        attr:class=move || if is_shared.get() { "text-green-500" } else { "text-muted-foreground hover:text-foreground" }
-                                                                                    >
-                                                                                        {move || {
-                                                                                            if is_shared.get() {
-                                                                                                view! {
-                                                                                                    <svg class="w-4 h-4 text-green-500" fill="none" stroke="currentColor" viewBox="0 0 24 24">
-                                                                                                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M5 13l4 4L19 7"></path>
-                                                                                                    </svg>
-                                                                                                }
-                                                                                            } else {
-                                                                                                view! {
-                                                                                                    <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
-                                                                                                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M8.684 13.342C8.886 12.938 9 12.482 9 12c0-.482-.114-.938-.316-1.342m0 2.684a3 3 0 110-2.684m0 2.684l6.632 3.316m-6.632-6l6.632-3.316m0 0a3 3 0 105.367-2.684 3 3 0 00-5.367 2.684zm0 9.316a3 3 0 105.367 2.684 3 3 0 00-5.367-2.684z"></path>
-                                                                                                    </svg>
-                                                                                                }
-                                                                                            }
-                                                                                        }}
-                                                                                    </Button>
-                                                                                }
-                                                                            }
-                                                                        </div>
-                                                                    </div>
-                                                                }
-                                                            })
-                                                            .collect_view()
-                                                        }
+                                                        {posts.into_iter()
+                                                            .map(|post| view! { <PostCard post=post/> })
+                                                            .collect_view()}
+                                                        {move || more_posts.get().into_iter()
+                                                            .map(|post| view! { <PostCard post=post/> })
+                                                            .collect_view()}
+                                                    </div>
+
+                                                    {(total_pages > 1).then(|| view! {
+                                                        <nav
+                                                            aria-label="Pagination"
+                                                            class=move || format!(
+                                                                "grid grid-cols-3 items-center mt-6 sm:mt-8 text-sm sm:text-base {}",
+                                                                if scrolling() { "hidden" } else { "" }
+                                                            )
+                                                        >
+                                                            <span>
+                                                                {(page > 1).then(|| view! {
+                                                                    <A href=page_href(page - 1) class="text-primary hover:text-primary/80">"← Newer posts"</A>
+                                                                })}
+                                                            </span>
+                                                            <span class="text-center text-muted-foreground">
+                                                                {format!("Page {} of {}", page, total_pages)}
+                                                            </span>
+                                                            <span class="text-right">
+                                                                {(page < total_pages).then(|| view! {
+                                                                    <A href=page_href(page + 1) class="text-primary hover:text-primary/80">"Older posts →"</A>
+                                                                })}
+                                                            </span>
+                                                        </nav>
+                                                    })}
+
+                                                    // Also loads more when the list doesn't fill the screen
+                                                    <div class=move || if scrolling() { "flex justify-center mt-6 sm:mt-8" } else { "hidden" }>
+                                                        <button
+                                                            class="text-sm sm:text-base text-primary hover:text-primary/80 cursor-pointer disabled:opacity-50"
+                                                            disabled=move || loading.get()
+                                                            on:click=move |_| load_more()
+                                                        >
+                                                            {move || if loading.get() { "Loading…" } else { "Load more posts" }}
+                                                        </button>
                                                     </div>
                                                 }.into_view()
                                             }
-                                        }.into_view(),
+                                        }
                                         Err(e) => view! {
                                             <p class="text-destructive text-center text-lg">"Error loading posts: " {e.to_string()}</p>
                                         }.into_view(),
                                     })
-                            }}
-                        </Suspense>
+                                    .into_view(),
+                            }
+                        }}
+                    </Transition>
                 </main>
             </div>
         </div>
@@ -0,0 +1,21 @@
+use actix_web::{get, HttpResponse};
+
+use crate::components::post::{index::post_index, preview_drafts};
+
+/// Posts left out of the index because they failed to parse, in the same format as
+/// `blog --check`.
+///
+/// Only served while drafts are previewed, since the report names files on disk and
+/// quotes draft content.
+#[get("/lint")]
+pub async fn lint_report() -> HttpResponse {
+    if !preview_drafts() {
+        return HttpResponse::NotFound().finish();
+    }
+    let index = post_index();
+    let report = index.read().unwrap().lint_report();
+
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(report)
+}
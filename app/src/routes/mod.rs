@@ -1,2 +1,19 @@
+pub mod archive;
+#[cfg(feature = "ssr")]
+pub mod feed;
 pub mod home;
+#[cfg(feature = "ssr")]
+pub mod lint;
 pub mod post;
+pub mod series;
+#[cfg(feature = "ssr")]
+pub mod sitemap;
+pub mod tags;
+
+/// Answer the page being rendered on the server with a 404; does nothing in the browser.
+pub fn set_not_found_status() {
+    #[cfg(feature = "ssr")]
+    if let Some(response) = leptos::use_context::<leptos_actix::ResponseOptions>() {
+        response.set_status(actix_web::http::StatusCode::NOT_FOUND);
+    }
+}
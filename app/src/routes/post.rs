@@ -3,23 +3,51 @@ use leptos_meta::*;
 use leptos_router::*;
 
 #[cfg(feature = "ssr")]
-use crate::components::post::{get_post_by_slug, increment_view, update_vote};
-use crate::components::post::{header::PostHeader, markdown::Markdown, PostInteractions};
+use crate::components::post::{
+    find_related_posts, get_post_by_slug, increment_view, render::render_post, update_vote,
+};
+use crate::components::post::{
+    header::PostHeader, markdown::MarkdownElements, related::ReadNext, series::SeriesNav,
+    toc::TableOfContents, use_section_links, PostInteractions, PostStatus,
+};
+use crate::routes::set_not_found_status;
 
 #[server(GetPost, "/api")]
 pub async fn get_post(
     slug: String,
 ) -> Result<Option<crate::components::post::BlogPost>, ServerFnError> {
-    eprintln!("Server function get_post called with slug: {}", slug);
-    let result = get_post_by_slug(&slug).await;
+    tracing::debug!("get_post called with slug: {}", slug);
+    // Rendered here so the client hydrates from the elements instead of parsing Markdown.
+    // A render can shell out to mermaid-cli and stat image files, so it runs on the blocking
+    // pool rather than an async worker.
+    let result = match get_post_by_slug(&slug).await {
+        Ok(Some(mut post)) => tokio::task::spawn_blocking(move || {
+            post.rendered = Some(render_post(&post));
+            // The client only reads the render, so the Markdown isn't sent twice
+            post.content.clear();
+            Some(post)
+        })
+        .await
+        .map_err(std::io::Error::other),
+        other => other,
+    };
     match &result {
-        Ok(Some(post)) => eprintln!("Successfully loaded post: {}", post.title),
-        Ok(None) => eprintln!("Post not found for slug: {}", slug),
-        Err(e) => eprintln!("Error loading post: {}", e),
+        Ok(Some(post)) => tracing::debug!("Loaded post: {}", post.title),
+        Ok(None) => tracing::debug!("Post not found for slug: {}", slug),
+        Err(e) => tracing::warn!("Error loading post {}: {}", slug, e),
     }
     result.map_err(|e| ServerFnError::ServerError(e.to_string()))
 }
 
+#[server(GetRelatedPosts, "/api")]
+pub async fn get_related_posts(
+    slug: String,
+) -> Result<crate::components::post::RelatedPosts, ServerFnError> {
+    find_related_posts(&slug)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
 #[server(TrackView, "/api")]
 pub async fn track_view(slug: String) -> Result<(), ServerFnError> {
     increment_view(&slug);
@@ -32,6 +60,25 @@ pub async fn submit_vote(slug: String, is_like: bool) -> Result<(), ServerFnErro
     Ok(())
 }
 
+// Jump to the section named in the URL once the post is on the page; the browser's own
+// attempt happens before streamed-in content exists
+#[cfg(feature = "hydrate")]
+fn scroll_to_location_hash() {
+    let Ok(hash) = window().location().hash() else {
+        return;
+    };
+    let Some(id) = hash.strip_prefix('#').filter(|id| !id.is_empty()) else {
+        return;
+    };
+    let id = js_sys::decode_uri_component(id)
+        .ok()
+        .and_then(|id| id.as_string())
+        .unwrap_or_else(|| id.to_string());
+    if let Some(element) = document().get_element_by_id(&id) {
+        element.scroll_into_view();
+    }
+}
+
 #[component]
 pub fn PostPage() -> impl IntoView {
     let params = use_params_map();
@@ -55,10 +102,20 @@ pub fn PostPage() -> impl IntoView {
                     post.get()
                         .map(|post| match post {
                             Ok(Some(post)) => {
+                                let parsed = post.rendered.clone().unwrap_or_default();
+                                use_section_links(post.slug.clone());
+                                #[cfg(feature = "hydrate")]
+                                create_effect(|_| scroll_to_location_hash());
+                                // A single heading isn't worth a table of contents
+                                let show_toc = parsed.headings.len() > 1
+                                    || parsed.headings.iter().any(|heading| !heading.children.is_empty());
                                 view! {
                                     <article>
                                         <Title text=post.title.clone()/>
                                         <Meta name="description" content=post.excerpt.clone()/>
+                                        {(post.status != PostStatus::Published).then(|| view! {
+                                            <Meta name="robots" content="noindex"/>
+                                        })}
 
                                         // Back button
                                         <A href="/" class="text-sm sm:text-base md:text-lg text-primary hover:text-primary/80 mb-6 sm:mb-7 md:mb-8 inline-block">
@@ -70,32 +127,48 @@ pub fn PostPage() -> impl IntoView {
                                             slug=post.slug.clone()
                                             title=post.title.clone()
                                             date=post.date
+                                            updated=post.updated
                                             excerpt=post.excerpt.clone()
                                             tags=post.tags.clone()
+                                            word_count=post.word_count
+                                            code_lines=post.code_lines
+                                            reading_time_minutes=post.reading_time_minutes
                                             initial_views=post.metrics.views
+                                            status=post.status
                                         />
 
+                                        {post.series.clone().map(|series| view! {
+                                            <SeriesNav series=series current=post.slug.clone()/>
+                                        })}
+
+                                        {show_toc.then(|| view! { <TableOfContents headings=parsed.headings/> })}
+
                                         // Post content
-                                        <Markdown content=post.content base_path=format!("{}.md", post.slug)/>
+                                        <MarkdownElements elements=parsed.elements/>
 
                                         // Post interactions (sharing)
                                         <PostInteractions
                                             slug=post.slug.clone()
                                         />
+
+                                        <ReadNext slug=post.slug.clone()/>
                                     </article>
                                 }.into_view()
                             }
-                            Ok(None) => view! {
-                                <Title text="Post not found - blog"/>
-                                <div class="text-center py-12">
-                                    <h1 class="text-3xl font-bold text-foreground mb-4">
-                                        "Post not found"
-                                    </h1>
-                                    <A href="/" class="text-primary hover:text-primary/80">
-                                        "Back to home"
-                                    </A>
-                                </div>
-                            }.into_view(),
+                            Ok(None) => {
+                                set_not_found_status();
+                                view! {
+                                    <Title text="Post not found - blog"/>
+                                    <div class="text-center py-12">
+                                        <h1 class="text-3xl font-bold text-foreground mb-4">
+                                            "Post not found"
+                                        </h1>
+                                        <A href="/" class="text-primary hover:text-primary/80">
+                                            "Back to home"
+                                        </A>
+                                    </div>
+                                }.into_view()
+                            }
                             Err(e) => view! {
                                 <Title text="Error - blog"/>
                                 <p class="text-destructive">"Error loading post: " {e.to_string()}</p>
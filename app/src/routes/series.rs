@@ -0,0 +1,114 @@
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+
+#[cfg(feature = "ssr")]
+use crate::components::post::list_series;
+use crate::components::post::{card::PostCard, series_href, Series};
+
+#[server(GetAllSeries, "/api")]
+pub async fn get_all_series() -> Result<Vec<Series>, ServerFnError> {
+    list_series()
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+#[server(GetSeries, "/api")]
+pub async fn get_series(slug: String) -> Result<Option<Series>, ServerFnError> {
+    let series = list_series()
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    Ok(series.into_iter().find(|series| series.slug == slug))
+}
+
+#[component]
+pub fn SeriesListPage() -> impl IntoView {
+    let series = create_resource(|| (), |_| async { get_all_series().await });
+
+    view! {
+        <div class="max-w-4xl mx-auto px-4 sm:px-6 pt-3 pb-12 sm:pb-16 min-h-screen">
+            <Title text="Series - blog"/>
+
+            <A href="/" class="text-sm sm:text-base md:text-lg text-primary hover:text-primary/80 mb-6 sm:mb-7 md:mb-8 inline-block">
+                "← Back to posts"
+            </A>
+
+            <h1 class="text-2xl sm:text-3xl md:text-4xl font-bold mb-6 sm:mb-8 text-foreground">"Series"</h1>
+
+            <Suspense fallback=move || view! { <div></div> }>
+                {move || {
+                    series.get()
+                        .map(|series| match series {
+                            Ok(series) if series.is_empty() => view! {
+                                <p class="text-muted-foreground text-lg">"No series yet"</p>
+                            }.into_view(),
+                            Ok(series) => view! {
+                                <ul class="space-y-3 sm:space-y-4">
+                                    {series.into_iter().map(|series| {
+                                        let parts = series.parts.len();
+                                        view! {
+                                            <li>
+                                                <A href=series_href(&series.name) class="text-lg sm:text-xl font-semibold text-foreground hover:text-primary">
+                                                    {series.name}
+                                                </A>
+                                                <span class="ml-2 text-sm text-muted-foreground">
+                                                    {format!("{} {}", parts, if parts == 1 { "part" } else { "parts" })}
+                                                </span>
+                                            </li>
+                                        }
+                                    }).collect_view()}
+                                </ul>
+                            }.into_view(),
+                            Err(e) => view! {
+                                <p class="text-destructive">"Error loading series: " {e.to_string()}</p>
+                            }.into_view(),
+                        })
+                }}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+pub fn SeriesPage() -> impl IntoView {
+    let params = use_params_map();
+    let slug = move || params.with(|params| params.get("series").cloned().unwrap_or_default());
+
+    let series = create_resource(slug, |slug| async move { get_series(slug).await });
+
+    view! {
+        <div class="max-w-4xl mx-auto px-4 sm:px-6 pt-3 pb-12 sm:pb-16 min-h-screen">
+            <A href="/series" class="text-sm sm:text-base md:text-lg text-primary hover:text-primary/80 mb-6 sm:mb-7 md:mb-8 inline-block">
+                "← All series"
+            </A>
+
+            <Suspense fallback=move || view! { <div></div> }>
+                {move || {
+                    series.get()
+                        .map(|series| match series {
+                            Ok(Some(series)) => view! {
+                                <Title text=format!("{} - blog", series.name)/>
+                                <h1 class="text-2xl sm:text-3xl md:text-4xl font-bold mb-2 text-foreground">
+                                    {series.name.clone()}
+                                </h1>
+                                <p class="text-muted-foreground mb-6 sm:mb-8">
+                                    {format!("A series in {} parts", series.parts.len())}
+                                </p>
+                                <ol class="space-y-2 sm:space-y-3 md:space-y-4">
+                                    {series.parts.into_iter()
+                                        .map(|post| view! { <li><PostCard post=post/></li> })
+                                        .collect_view()}
+                                </ol>
+                            }.into_view(),
+                            Ok(None) => view! {
+                                <p class="text-muted-foreground text-lg">"Series not found"</p>
+                            }.into_view(),
+                            Err(e) => view! {
+                                <p class="text-destructive">"Error loading series: " {e.to_string()}</p>
+                            }.into_view(),
+                        })
+                }}
+            </Suspense>
+        </div>
+    }
+}
@@ -0,0 +1,68 @@
+use actix_web::{get, HttpResponse};
+use chrono::SecondsFormat;
+use html_escape::encode_text as text;
+
+use super::feed::site_url;
+use crate::components::post::get_all_posts;
+
+#[get("/sitemap.xml")]
+pub async fn sitemap() -> HttpResponse {
+    let posts = match get_all_posts().await {
+        Ok(posts) => posts,
+        Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
+    };
+    let base = site_url();
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+
+    // The home page changes whenever any post does
+    xml.push_str("<url>");
+    xml.push_str(&format!("<loc>{}/</loc>", text(base)));
+    if let Some(latest) = posts.iter().map(|post| post.last_modified()).max() {
+        xml.push_str(&format!(
+            "<lastmod>{}</lastmod>",
+            latest.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ));
+    }
+    xml.push_str("</url>");
+
+    for post in &posts {
+        xml.push_str("<url>");
+        xml.push_str(&format!(
+            "<loc>{}/post/{}</loc>",
+            text(base),
+            text(&post.slug)
+        ));
+        xml.push_str(&format!(
+            "<lastmod>{}</lastmod>",
+            post.last_modified()
+                .to_rfc3339_opts(SecondsFormat::Secs, true)
+        ));
+        xml.push_str("</url>");
+    }
+
+    xml.push_str("</urlset>");
+
+    HttpResponse::Ok()
+        .content_type("application/xml; charset=utf-8")
+        .body(xml)
+}
+
+/// `robots.txt` allowing everything except the server function API, plus any
+/// comma-separated paths listed in `BLOG_ROBOTS_DISALLOW`.
+#[get("/robots.txt")]
+pub async fn robots() -> HttpResponse {
+    let base = site_url();
+    let extra = std::env::var("BLOG_ROBOTS_DISALLOW").unwrap_or_default();
+
+    let mut body = String::from("User-agent: *\nAllow: /\nDisallow: /api/\n");
+    for path in extra.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        body.push_str(&format!("Disallow: {}\n", path));
+    }
+    body.push_str(&format!("\nSitemap: {}/sitemap.xml\n", base));
+
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(body)
+}
@@ -0,0 +1,160 @@
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::components::post::{card::PostCard, PostSummary, TagCount};
+#[cfg(feature = "ssr")]
+use crate::components::post::{count_tags, get_all_posts};
+use crate::components::ui::badge::{Badge, BadgeSize, BadgeVariant};
+
+#[server(GetTags, "/api")]
+pub async fn get_tags() -> Result<Vec<TagCount>, ServerFnError> {
+    let posts = get_all_posts()
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    Ok(count_tags(&posts))
+}
+
+#[server(GetPostsByTag, "/api")]
+pub async fn get_posts_by_tag(tag: String) -> Result<Vec<PostSummary>, ServerFnError> {
+    let posts = get_all_posts()
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    Ok(posts
+        .into_iter()
+        .filter(|post| post.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)))
+        .map(PostSummary::from)
+        .collect())
+}
+
+// Everything but unreserved URL characters is escaped, so "c#", "ci/cd" or "c++" stay a
+// single path segment
+const TAG_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'~');
+
+// Link to the listing for a single tag
+pub fn tag_href(tag: &str) -> String {
+    format!("/tag/{}", utf8_percent_encode(tag, TAG_SEGMENT))
+}
+
+// The tag named by a `/tag/:tag` segment; the router hands over the segment still escaped
+fn tag_from_segment(segment: &str) -> String {
+    percent_decode_str(segment).decode_utf8_lossy().into_owned()
+}
+
+#[component]
+pub fn TagsPage() -> impl IntoView {
+    let tags = create_resource(|| (), |_| async { get_tags().await });
+
+    view! {
+        <div class="max-w-4xl mx-auto px-4 sm:px-6 pt-3 pb-12 sm:pb-16 min-h-screen">
+            <Title text="Tags - blog"/>
+
+            <A href="/" class="text-sm sm:text-base md:text-lg text-primary hover:text-primary/80 mb-6 sm:mb-7 md:mb-8 inline-block">
+                "← Back to posts"
+            </A>
+
+            <h1 class="text-2xl sm:text-3xl md:text-4xl font-bold mb-6 sm:mb-8 text-foreground">"Tags"</h1>
+
+            <Suspense fallback=move || view! { <div></div> }>
+                {move || {
+                    tags.get()
+                        .map(|tags| match tags {
+                            Ok(tags) if tags.is_empty() => view! {
+                                <p class="text-muted-foreground text-lg">"No tags yet"</p>
+                            }.into_view(),
+                            Ok(tags) => view! {
+                                <div class="flex flex-wrap gap-2 sm:gap-3">
+                                    {tags.into_iter().map(|TagCount { tag, count }| {
+                                        view! {
+                                            <A href=tag_href(&tag)>
+                                                <Badge variant=BadgeVariant::Primary size=BadgeSize::Large>
+                                                    {"#"}{tag}
+                                                    <span class="ml-2 text-muted-foreground">{count}</span>
+                                                </Badge>
+                                            </A>
+                                        }
+                                    }).collect_view()}
+                                </div>
+                            }.into_view(),
+                            Err(e) => view! {
+                                <p class="text-destructive">"Error loading tags: " {e.to_string()}</p>
+                            }.into_view(),
+                        })
+                }}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+pub fn TagPage() -> impl IntoView {
+    let params = use_params_map();
+    let tag = move || {
+        params.with(|params| {
+            params
+                .get("tag")
+                .map(|tag| tag_from_segment(tag))
+                .unwrap_or_default()
+        })
+    };
+
+    let posts = create_resource(tag, |tag| async move { get_posts_by_tag(tag).await });
+
+    view! {
+        <div class="max-w-4xl mx-auto px-4 sm:px-6 pt-3 pb-12 sm:pb-16 min-h-screen">
+            <Title text=move || format!("#{} - blog", tag())/>
+
+            <A href="/tags" class="text-sm sm:text-base md:text-lg text-primary hover:text-primary/80 mb-6 sm:mb-7 md:mb-8 inline-block">
+                "← All tags"
+            </A>
+
+            <h1 class="text-2xl sm:text-3xl md:text-4xl font-bold mb-6 sm:mb-8 text-foreground">
+                {"#"}{tag}
+            </h1>
+
+            <Suspense fallback=move || view! { <div></div> }>
+                {move || {
+                    posts.get()
+                        .map(|posts| match posts {
+                            Ok(posts) if posts.is_empty() => view! {
+                                <p class="text-muted-foreground text-lg">"No posts with this tag"</p>
+                            }.into_view(),
+                            Ok(posts) => view! {
+                                <div class="space-y-2 sm:space-y-3 md:space-y-4">
+                                    {posts.into_iter()
+                                        .map(|post| view! { <PostCard post=post/> })
+                                        .collect_view()}
+                                </div>
+                            }.into_view(),
+                            Err(e) => view! {
+                                <p class="text-destructive">"Error loading posts: " {e.to_string()}</p>
+                            }.into_view(),
+                        })
+                }}
+            </Suspense>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_links_escape_reserved_characters() {
+        assert_eq!(tag_href("rust"), "/tag/rust");
+        assert_eq!(tag_href("c#"), "/tag/c%23");
+        assert_eq!(tag_href("ci/cd"), "/tag/ci%2Fcd");
+        assert_eq!(tag_href("c++"), "/tag/c%2B%2B");
+        assert_eq!(tag_href("what? now"), "/tag/what%3F%20now");
+    }
+
+    #[test]
+    fn escaped_tags_round_trip() {
+        for tag in ["c#", "ci/cd", "c++", "what? now", "100%", "日本語"] {
+            let segment = tag_href(tag).strip_prefix("/tag/").unwrap().to_string();
+            assert_eq!(tag_from_segment(&segment), tag);
+        }
+    }
+}